@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use bevy::reflect::erased_serde::__private::serde::{Deserialize, Serialize};
+
+pub const NORTH: u8 = 1;
+pub const EAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const WEST: u8 = 8;
+pub const NORTH_EAST: u8 = 16;
+pub const SOUTH_EAST: u8 = 32;
+pub const SOUTH_WEST: u8 = 64;
+pub const NORTH_WEST: u8 = 128;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutotileMode {
+    // 4-bit mask of the north, east, south and west neighbours.
+    #[default]
+    Cardinal,
+    // 8-bit mask where a diagonal only counts when both cardinals next to it are set, so inner corners
+    // can get their own tiles.
+    Blob,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AutotileRule {
+    #[serde(default)]
+    pub mode: AutotileMode,
+    pub default: usize,
+    pub tiles: HashMap<u8, usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<usize>,
+}
+
+impl AutotileRule {
+    pub fn full_mask(&self) -> u8 {
+        match self.mode {
+            AutotileMode::Cardinal => NORTH | EAST | SOUTH | WEST,
+            AutotileMode::Blob => u8::MAX,
+        }
+    }
+
+    // Rows grow downwards, so north is y - 1.
+    pub fn neighbour_mask(&self, x: isize, y: isize, is_terrain: impl Fn(isize, isize) -> bool) -> u8 {
+        let mut mask = 0;
+        for (bit, dx, dy) in [(NORTH, 0, -1), (EAST, 1, 0), (SOUTH, 0, 1), (WEST, -1, 0)] {
+            if is_terrain(x + dx, y + dy) {
+                mask |= bit;
+            }
+        }
+
+        if self.mode == AutotileMode::Blob {
+            for (bit, dx, dy, sides) in [
+                (NORTH_EAST, 1, -1, NORTH | EAST),
+                (SOUTH_EAST, 1, 1, SOUTH | EAST),
+                (SOUTH_WEST, -1, 1, SOUTH | WEST),
+                (NORTH_WEST, -1, -1, NORTH | WEST),
+            ] {
+                if mask & sides == sides && is_terrain(x + dx, y + dy) {
+                    mask |= bit;
+                }
+            }
+        }
+
+        mask
+    }
+
+    pub fn tile_for_mask(&self, mask: u8) -> usize {
+        self.tiles.get(&mask).copied().unwrap_or(self.default)
+    }
+
+    pub fn contains_tile(&self, tile: usize) -> bool {
+        tile == self.default || self.tiles.values().any(|t| *t == tile) || self.variants.contains(&tile)
+    }
+
+    pub fn autotile_region(&self, region: &[Vec<bool>]) -> Vec<Vec<Option<usize>>> {
+        let is_terrain = |x: isize, y: isize| {
+            x >= 0 && y >= 0 && region.get(y as usize).and_then(|row| row.get(x as usize)).copied().unwrap_or(false)
+        };
+
+        region.iter().enumerate().map(|(y, row)| {
+            row.iter().enumerate().map(|(x, inside)| {
+                inside.then(|| self.tile_for_mask(self.neighbour_mask(x as isize, y as isize, is_terrain)))
+            }).collect()
+        }).collect()
+    }
+}
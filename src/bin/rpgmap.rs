@@ -0,0 +1,193 @@
+use std::process::ExitCode;
+use rpggame::generator::{self, GeneratorParams};
+use rpggame::ldtk::{self, LdtkImportOptions};
+use rpggame::map::{Block, Map, MapError, MapLayer};
+use rpggame::map_render::{self, RenderOptions};
+use rpggame::map_stats::MapStats;
+use rpggame::tiled::{self, TiledImportOptions};
+use rpggame::tileset::{TileOverrides, TilesetManifest};
+
+const DEFAULT_TILESETS: &str = "assets/tilesets.json";
+const ASSETS_ROOT: &str = "assets";
+
+const USAGE: &str = "usage:
+  rpgmap validate <map> [--tilesets <path>]
+  rpgmap convert <input> <output> [--compress]
+  rpgmap stats <map> [--tilesets <path>]
+  rpgmap generate <params.json> <output> [--seed <seed>] [--tilesets <path>] [--compress]
+  rpgmap render <map> <output.png> [--layer <layer>]... [--per-layer] [--collision] [--tilesets <path>]
+  rpgmap export <map> <output.tmj> [--tilesets <path>]
+  rpgmap import <input.tmj|input.ldtk> <output> [--chunk-size <size>] [--fill <texture>:<tile>] [--level <name>] [--compress]
+
+Maps ending in .map.bin are written in the binary format, anything else as JSON.
+LDtk entities are written next to the imported map as <output>.entities.json.";
+
+struct Args {
+    positional: Vec<String>,
+    tilesets: String,
+    seed: Option<u64>,
+    compress: bool,
+    layers: Vec<MapLayer>,
+    collision: bool,
+    per_layer: bool,
+    chunk_size: usize,
+    fill: Option<Block>,
+    level: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        positional: Vec::new(),
+        tilesets: DEFAULT_TILESETS.to_string(),
+        seed: None,
+        compress: false,
+        layers: Vec::new(),
+        collision: false,
+        per_layer: false,
+        chunk_size: 16,
+        fill: None,
+        level: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tilesets" => parsed.tilesets = args.next().ok_or("--tilesets needs a path")?,
+            "--seed" => {
+                let seed = args.next().ok_or("--seed needs a value")?;
+                parsed.seed = Some(seed.parse().map_err(|_| format!("invalid seed \"{}\"", seed))?);
+            }
+            "--compress" => parsed.compress = true,
+            "--collision" => parsed.collision = true,
+            "--per-layer" => parsed.per_layer = true,
+            "--layer" => {
+                let name = args.next().ok_or("--layer needs a layer name")?;
+                parsed.layers.push(MapLayer::from_name(&name).ok_or_else(|| format!("unknown layer \"{}\"", name))?);
+            }
+            "--chunk-size" => {
+                let size = args.next().ok_or("--chunk-size needs a value")?;
+                parsed.chunk_size = size.parse().map_err(|_| format!("invalid chunk size \"{}\"", size))?;
+            }
+            "--fill" => {
+                let fill = args.next().ok_or("--fill needs a <texture>:<tile> value")?;
+                let (texture, tile) = fill.rsplit_once(':').ok_or_else(|| format!("invalid fill \"{}\"", fill))?;
+                let tile = tile.parse().map_err(|_| format!("invalid fill \"{}\"", fill))?;
+                parsed.fill = Some(Block { tile, texture: texture.to_string(), overrides: TileOverrides::default() });
+            }
+            "--level" => parsed.level = Some(args.next().ok_or("--level needs a level name")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => parsed.positional.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn run(command: &str, args: Args) -> Result<(), String> {
+    let error = |error: MapError| error.to_string();
+    match (command, args.positional.as_slice()) {
+        ("validate", [path]) => {
+            let map = Map::load_from_file(path).map_err(error)?;
+            let tilesets = TilesetManifest::load_from_json(&args.tilesets).map_err(error)?;
+            map.validate_tiles(&tilesets.tile_counts()).map_err(error)?;
+            println!("{}: ok", path);
+        }
+        ("convert", [input, output]) => {
+            let map = Map::load_from_file(input).map_err(error)?;
+            map.save_to_file(output, args.compress).map_err(error)?;
+            println!("{} -> {}", input, output);
+        }
+        ("stats", [path]) => {
+            let map = Map::load_from_file(path).map_err(error)?;
+            let tilesets = TilesetManifest::load_from_json(&args.tilesets).map_err(error)?;
+            print!("{}", MapStats::collect(&map, &tilesets));
+        }
+        ("generate", [params_path, output]) => {
+            let params = std::fs::read_to_string(params_path).map_err(|e| format!("could not read {}: {}", params_path, e))?;
+            let mut params: GeneratorParams = serde_json::from_str(&params).map_err(|e| format!("invalid generator params: {}", e))?;
+            if let Some(seed) = args.seed {
+                params.seed = seed;
+            }
+            let tilesets = TilesetManifest::load_from_json(&args.tilesets).map_err(error)?;
+            let map = generator::generate(&params, &tilesets).map_err(|e| e.to_string())?;
+            map.save_to_file(output, args.compress).map_err(error)?;
+            println!("generated {} with seed {}", output, params.seed);
+        }
+        ("render", [path, output]) => {
+            let map = Map::load_from_file(path).map_err(error)?;
+            let tilesets = TilesetManifest::load_from_json(&args.tilesets).map_err(error)?;
+            let images = map_render::load_tileset_images(&tilesets, ASSETS_ROOT).map_err(error)?;
+            let mut options = RenderOptions { collision_overlay: args.collision, ..Default::default() };
+            if !args.layers.is_empty() {
+                options.layers = args.layers;
+            }
+            if args.per_layer {
+                for (layer, image) in map_render::render_map_layers(&map, &tilesets, &images, &options).map_err(error)? {
+                    let layer_output = map_render::layer_image_path(output, layer);
+                    image.save(&layer_output).map_err(|e| format!("could not write {}: {}", layer_output, e))?;
+                    println!("rendered the {:?} layer of {} to {}", layer, path, layer_output);
+                }
+                return Ok(());
+            }
+            let image = map_render::render_map(&map, &tilesets, &images, &options).map_err(error)?;
+            image.save(output).map_err(|e| format!("could not write {}: {}", output, e))?;
+            println!("rendered {} to {}", path, output);
+        }
+        ("export", [path, output]) => {
+            let map = Map::load_from_file(path).map_err(error)?;
+            let tilesets = TilesetManifest::load_from_json(&args.tilesets).map_err(error)?;
+            for warning in tiled::export_tiled_file(&map, &tilesets, ASSETS_ROOT, output).map_err(error)? {
+                eprintln!("warning: {}", warning);
+            }
+            println!("exported {} -> {}", path, output);
+        }
+        ("import", [input, output]) => {
+            let fill = match args.fill {
+                Some(fill) => fill,
+                None => {
+                    let tilesets = TilesetManifest::load_from_json(&args.tilesets).map_err(error)?;
+                    let tileset = tilesets.tilesets.first().ok_or("no tilesets to fill empty cells from, pass --fill")?;
+                    Block { tile: 0, texture: tileset.name.clone(), overrides: TileOverrides::default() }
+                }
+            };
+            let (map, warnings) = if input.ends_with(".ldtk") {
+                let options = LdtkImportOptions { chunk_size: args.chunk_size, level: args.level, fill };
+                let import = ldtk::import_ldtk_file(input, &options).map_err(error)?;
+                let entities_path = format!("{}.entities.json", map_stem(output));
+                let entities = serde_json::to_string_pretty(&import.entities).map_err(|e| e.to_string())?;
+                std::fs::write(&entities_path, entities).map_err(|e| format!("could not write {}: {}", entities_path, e))?;
+                (import.map, import.warnings)
+            } else {
+                let options = TiledImportOptions { chunk_size: args.chunk_size, fill };
+                let import = tiled::import_tiled_file(input, &options).map_err(error)?;
+                (import.map, import.warnings)
+            };
+            for warning in &warnings {
+                eprintln!("warning: {}", warning);
+            }
+            map.save_to_file(output, args.compress).map_err(error)?;
+            println!("imported {} -> {}", input, output);
+        }
+        _ => return Err(USAGE.to_string()),
+    }
+    Ok(())
+}
+
+fn map_stem(path: &str) -> &str {
+    [".map.json", ".map.bin", ".json"].iter()
+        .find_map(|extension| path.strip_suffix(extension))
+        .unwrap_or(path)
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    match parse_args(args).and_then(|args| run(&command, args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
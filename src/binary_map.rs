@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::Value;
+use crate::map::{Block, Chunk, Map, MapError, MapLayer};
+use crate::migrations::{self, CURRENT_VERSION};
+
+pub const MAGIC: &[u8; 4] = b"RPGM";
+pub const VERSION: u8 = 2;
+// Version 1 files carry no schema version; their blocks have the shape of schema version 2.
+const V1_SCHEMA_VERSION: u64 = 2;
+const FLAG_COMPRESSED: u8 = 1;
+// Runs expand a few bytes into many blocks, so decoded sizes are capped to keep a corrupt file from
+// exhausting memory.
+const MAX_BODY_LEN: u64 = 64 << 20;
+const MAX_BLOCKS: usize = 1 << 24;
+
+// Layout: magic, version, flags, the map schema version the palette blocks follow, then the
+// (optionally deflated) body:
+//   chunk_size, row count, and per row its chunk count
+//   palette of distinct blocks, each stored as length-prefixed JSON
+//   per chunk: the Base grid as (run length, palette index) pairs, then the overlay layers as
+//   (layer, runs) where palette index 0 means an empty cell.
+// All integers are LEB128 varints.
+pub fn encode(map: &Map, compress: bool) -> Result<Vec<u8>, MapError> {
+    let mut palette: Vec<String> = Vec::new();
+    let mut palette_index: HashMap<String, usize> = HashMap::new();
+    let mut index_of = |block: &Block| -> Result<usize, MapError> {
+        let json = serde_json::to_string(block)?;
+        Ok(*palette_index.entry(json.clone()).or_insert_with(|| {
+            palette.push(json);
+            palette.len() - 1
+        }))
+    };
+
+    let mut chunks_body = Vec::new();
+    for row in &map.chunks {
+        for chunk in row {
+            let base = chunk.blocks.iter().flatten().map(&mut index_of).collect::<Result<Vec<_>, _>>()?;
+            write_runs(&mut chunks_body, &base);
+
+            write_varint(&mut chunks_body, chunk.layers.len());
+            for (layer, grid) in &chunk.layers {
+                let cells = grid.iter().flatten().map(|cell| match cell {
+                    Some(block) => index_of(block).map(|index| index + 1),
+                    None => Ok(0),
+                }).collect::<Result<Vec<_>, _>>()?;
+                chunks_body.push(layer_id(*layer));
+                write_runs(&mut chunks_body, &cells);
+            }
+        }
+    }
+
+    let mut body = Vec::new();
+    write_varint(&mut body, map.chunk_size);
+    write_varint(&mut body, map.chunks.len());
+    for row in &map.chunks {
+        write_varint(&mut body, row.len());
+    }
+    write_varint(&mut body, palette.len());
+    for json in &palette {
+        write_varint(&mut body, json.len());
+        body.extend_from_slice(json.as_bytes());
+    }
+    body.extend_from_slice(&chunks_body);
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    bytes.push(if compress { FLAG_COMPRESSED } else { 0 });
+    write_varint(&mut bytes, CURRENT_VERSION as usize);
+    if compress {
+        let mut encoder = DeflateEncoder::new(bytes, Compression::best());
+        encoder.write_all(&body)?;
+        Ok(encoder.finish()?)
+    } else {
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+}
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Map, MapError> {
+    if !is_binary(bytes) || bytes.len() < 6 {
+        return Err(invalid("missing RPGM header"));
+    }
+    let mut header = Reader { bytes, position: 6 };
+    let schema_version = match bytes[4] {
+        1 => V1_SCHEMA_VERSION,
+        VERSION => header.varint()? as u64,
+        version => return Err(MapError::Binary(format!("unsupported binary map version {}", version))),
+    };
+    if schema_version > CURRENT_VERSION {
+        return Err(MapError::UnsupportedVersion { found: schema_version, supported: CURRENT_VERSION });
+    }
+    let body_start = header.position;
+
+    let body = if bytes[5] & FLAG_COMPRESSED != 0 {
+        let mut body = Vec::new();
+        DeflateDecoder::new(&bytes[body_start..]).take(MAX_BODY_LEN + 1).read_to_end(&mut body)?;
+        if body.len() as u64 > MAX_BODY_LEN {
+            return Err(invalid("decompressed body is too large"));
+        }
+        body
+    } else {
+        bytes[body_start..].to_vec()
+    };
+    let mut reader = Reader { bytes: &body, position: 0 };
+
+    let chunk_size = reader.varint()?;
+    let row_count = reader.count(1)?;
+    let row_lengths = (0..row_count).map(|_| reader.varint()).collect::<Result<Vec<_>, _>>()?;
+    let palette_len = reader.count(1)?;
+    let palette = (0..palette_len).map(|_| {
+        let len = reader.varint()?;
+        Ok(serde_json::from_slice::<Value>(reader.take(len)?)?)
+    }).collect::<Result<Vec<Value>, MapError>>()?;
+    let palette = migrate_palette(palette, schema_version, chunk_size)?;
+
+    // A chunk takes at least a layer count, plus one two-byte run per grid unless it has no cells.
+    let cell_count = chunk_size.checked_mul(chunk_size).ok_or_else(|| invalid("chunk size is too large"))?;
+    let grid_len = if cell_count == 0 { 0 } else { 2 };
+    let chunk_count = row_lengths.iter().try_fold(0usize, |total, length| total.checked_add(*length))
+        .filter(|count| count.saturating_mul(grid_len + 1) <= reader.remaining())
+        .ok_or_else(|| invalid("more chunks than the data can hold"))?;
+    if cell_count.checked_mul(chunk_count).is_none_or(|blocks| blocks > MAX_BLOCKS) {
+        return Err(invalid("map has too many blocks"));
+    }
+    let mut chunks = Vec::with_capacity(row_count);
+    for row_length in row_lengths {
+        let mut row = Vec::with_capacity(row_length);
+        for _ in 0..row_length {
+            let base = reader.runs(cell_count)?.into_iter()
+                .map(|index| palette.get(index).cloned().ok_or_else(|| invalid("palette index out of range")))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut layers = BTreeMap::new();
+            for _ in 0..reader.count(grid_len + 1)? {
+                let layer = layer_from_id(reader.byte()?)?;
+                let cells = reader.runs(cell_count)?.into_iter().map(|index| match index {
+                    0 => Ok(None),
+                    index => palette.get(index - 1).cloned().map(Some).ok_or_else(|| invalid("palette index out of range")),
+                }).collect::<Result<Vec<_>, _>>()?;
+                layers.insert(layer, to_grid(cells, chunk_size));
+            }
+
+            row.push(Chunk { blocks: to_grid(base, chunk_size), layers });
+        }
+        chunks.push(row);
+    }
+    if reader.position != body.len() {
+        return Err(invalid("trailing bytes after the last chunk"));
+    }
+
+    Ok(Map { chunk_size, chunks })
+}
+
+// Runs the palette through the JSON migrations as the only row of a one-chunk map, so blocks saved
+// with an older schema get the same upgrades as blocks in JSON maps.
+fn migrate_palette(palette: Vec<Value>, schema_version: u64, chunk_size: usize) -> Result<Vec<Block>, MapError> {
+    let map = serde_json::json!({ "version": schema_version, "chunk_size": chunk_size, "chunks": [[{ "blocks": [palette] }]] });
+    match migrations::migrate(map)?.pointer_mut("/chunks/0/0/blocks/0").map(Value::take) {
+        Some(blocks) => Ok(serde_json::from_value(blocks)?),
+        None => Err(invalid("palette was lost while migrating")),
+    }
+}
+
+fn invalid(message: &str) -> MapError {
+    MapError::Binary(message.to_string())
+}
+
+fn to_grid<T>(cells: Vec<T>, chunk_size: usize) -> Vec<Vec<T>> {
+    let mut cells = cells.into_iter();
+    (0..chunk_size).map(|_| cells.by_ref().take(chunk_size).collect()).collect()
+}
+
+fn layer_id(layer: MapLayer) -> u8 {
+    MapLayer::ALL.iter().position(|l| *l == layer).unwrap_or(0) as u8
+}
+
+fn layer_from_id(id: u8) -> Result<MapLayer, MapError> {
+    MapLayer::ALL.get(id as usize).copied().ok_or_else(|| invalid("unknown map layer"))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_runs(out: &mut Vec<u8>, values: &[usize]) {
+    let mut iter = values.iter().peekable();
+    while let Some(value) = iter.next() {
+        let mut run = 1;
+        while iter.next_if_eq(&value).is_some() {
+            run += 1;
+        }
+        write_varint(out, run);
+        write_varint(out, *value);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, MapError> {
+        let byte = *self.bytes.get(self.position).ok_or_else(|| invalid("unexpected end of data"))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    // Reads the number of items that follow, each taking at least item_len bytes, and rejects counts
+    // the rest of the data cannot hold.
+    fn count(&mut self, item_len: usize) -> Result<usize, MapError> {
+        let count = self.varint()?;
+        if count.checked_mul(item_len).is_none_or(|len| len > self.remaining()) {
+            return Err(invalid("count exceeds the remaining data"));
+        }
+        Ok(count)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MapError> {
+        let end = self.position.checked_add(len).filter(|end| *end <= self.bytes.len()).ok_or_else(|| invalid("unexpected end of data"))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<usize, MapError> {
+        let mut value = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint is too long"))
+    }
+
+    fn runs(&mut self, count: usize) -> Result<Vec<usize>, MapError> {
+        let mut values = Vec::with_capacity(count);
+        while values.len() < count {
+            let run = self.varint()?;
+            let value = self.varint()?;
+            if run == 0 || run > count - values.len() {
+                return Err(invalid("run length does not match the chunk size"));
+            }
+            values.extend(std::iter::repeat_n(value, run));
+        }
+        Ok(values)
+    }
+}
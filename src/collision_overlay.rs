@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use crate::coords;
+use crate::editor::EditorState;
+use crate::layers::EDITOR_LAYER;
+use crate::map::{LoadedMap, MapChunk};
+use crate::resolution::Resolution;
+use crate::tileset::TilesetRegistry;
+use crate::tools::EditorTool;
+
+pub struct CollisionOverlayPlugin;
+
+const TOGGLE_KEY: KeyCode = KeyCode::KeyO;
+
+#[derive(Resource, Default)]
+pub struct CollisionOverlay {
+    pub enabled: bool,
+}
+
+// Marks chunks whose tint sprites were spawned; the sprites are children of the chunk, so they are
+// respawned together with it after an edit.
+#[derive(Component)]
+struct CollisionTinted;
+
+#[derive(Component)]
+struct CollisionTint;
+
+impl Plugin for CollisionOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(CollisionOverlay::default())
+            .add_systems(Update, (
+                toggle_collision_overlay,
+                update_collision_overlay.run_if(resource_exists::<LoadedMap>.and(resource_exists::<TilesetRegistry>)),
+            ).chain());
+    }
+}
+
+// The overlay is always shown while painting collision.
+pub fn overlay_visible(overlay: &CollisionOverlay, state: &EditorState) -> bool {
+    state.enabled && (overlay.enabled || state.tool == EditorTool::Collision)
+}
+
+fn toggle_collision_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    state: Res<EditorState>,
+    mut overlay: ResMut<CollisionOverlay>,
+) {
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if state.enabled && !ctrl && keyboard_input.just_pressed(TOGGLE_KEY) {
+        overlay.enabled = !overlay.enabled;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_collision_overlay(
+    mut commands: Commands,
+    overlay: Res<CollisionOverlay>,
+    state: Res<EditorState>,
+    loaded_map: Res<LoadedMap>,
+    tilesets: Res<TilesetRegistry>,
+    resolution: Res<Resolution>,
+    q_untinted: Query<(Entity, &MapChunk), Without<CollisionTinted>>,
+    q_tinted: Query<Entity, With<CollisionTinted>>,
+    q_tints: Query<Entity, With<CollisionTint>>,
+) {
+    if !overlay_visible(&overlay, &state) {
+        for entity in q_tints.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        for entity in q_tinted.iter() {
+            commands.entity(entity).remove::<CollisionTinted>();
+        }
+        return;
+    }
+
+    let map = &loaded_map.map;
+    let block_size = coords::block_size(&resolution);
+    for (entity, MapChunk(chunk)) in q_untinted.iter() {
+        let origin = coords::chunk_to_block(*chunk, UVec2::ZERO, map.chunk_size);
+        commands.entity(entity).insert(CollisionTinted).with_children(|parent| {
+            for i in 0..map.chunk_size {
+                for j in 0..map.chunk_size {
+                    let (x, y) = ((origin.x + j as i32) as isize, (origin.y + i as i32) as isize);
+                    let Some(properties) = map.properties_at(x, y, &tilesets.manifest) else {
+                        continue;
+                    };
+                    let [red, green, blue, alpha] = properties.collision.tint();
+                    parent.spawn((
+                        Sprite::from_color(Color::srgba_u8(red, green, blue, alpha), block_size),
+                        Transform::from_translation(coords::block_to_world(IVec2::new(j as i32, i as i32), &resolution).extend(50.0)),
+                        RenderLayers::layer(EDITOR_LAYER),
+                        CollisionTint,
+                    ));
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use crate::resolution::Resolution;
+
+// Conversions between screen pixels, world units, map blocks and chunks. Block positions are in map space:
+// x grows to the right and y is the row, growing downwards, while world y grows upwards. Blocks are
+// centred on their world translation, so each block reaches half a block either side of it.
+
+pub fn block_size(resolution: &Resolution) -> Vec2 {
+    resolution.frame_size.as_vec2() * resolution.pixel_ratio as f32
+}
+
+pub fn block_to_world(block: IVec2, resolution: &Resolution) -> Vec2 {
+    block.as_vec2() * resolution.map_translation
+}
+
+pub fn world_to_block(position: Vec2, resolution: &Resolution) -> IVec2 {
+    (position / resolution.map_translation + Vec2::splat(0.5)).floor().as_ivec2()
+}
+
+// Chunk holding a block and the block's column and row inside it. Negative blocks fall into negative
+// chunks, so the local position is always within 0..chunk_size.
+pub fn block_to_chunk(block: IVec2, chunk_size: usize) -> (IVec2, UVec2) {
+    let size = IVec2::splat(chunk_size as i32);
+    (block.div_euclid(size), block.rem_euclid(size).as_uvec2())
+}
+
+pub fn chunk_to_block(chunk: IVec2, local: UVec2, chunk_size: usize) -> IVec2 {
+    chunk * chunk_size as i32 + local.as_ivec2()
+}
+
+// World translation of a chunk, which is the translation of its top-left block.
+pub fn chunk_to_world(chunk: IVec2, chunk_size: usize, resolution: &Resolution) -> Vec2 {
+    block_to_world(chunk_to_block(chunk, UVec2::ZERO, chunk_size), resolution)
+}
+
+pub fn world_to_chunk(position: Vec2, chunk_size: usize, resolution: &Resolution) -> IVec2 {
+    block_to_chunk(world_to_block(position, resolution), chunk_size).0
+}
+
+// Cursor position in window pixels (origin top-left, y down) to world units. Goes through the camera's
+// viewport and projection, so it follows window resizes and zoom.
+pub fn screen_to_world(camera: &Camera, camera_transform: &GlobalTransform, cursor: Vec2) -> Option<Vec2> {
+    camera.viewport_to_world_2d(camera_transform, cursor).ok()
+}
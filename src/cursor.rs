@@ -4,7 +4,7 @@ use bevy::window::PrimaryWindow;
 use crate::game::MainCamera;
 use crate::layers::{CURSOR_LAYER};
 
-pub(crate) struct CursorPlugin;
+pub struct CursorPlugin;
 
 #[derive(Component)]
 struct GameCursor {}
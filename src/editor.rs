@@ -0,0 +1,577 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::ui::RelativeCursorPosition;
+use crate::coords;
+use crate::gridselector::{BlockSelection, HoveredBlock};
+use crate::history::{changed_chunks, BlockChange, EditCommand, EditHistory, EditKind};
+use crate::map::{Block, ChunkChanged, Collision, LoadedMap, Map, MapLayer, MapReplaced, MAP_PATH};
+use crate::tileset::{TileOverrides, TilesetManifest, TilesetRegistry};
+use crate::layers::{LayerVisibility, EDITOR_LAYER};
+use crate::resolution::Resolution;
+use crate::tools::{
+    apply_stamp, flood_fill_cells, line_cells, list_prefabs, paint_cells, rectangle_cells, save_prefab, EditorTool, Stamp,
+    FLOOD_FILL_LIMIT, PREFAB_DIR,
+};
+
+pub struct EditorPlugin;
+
+const TOGGLE_KEY: KeyCode = KeyCode::F1;
+const TOOL_KEYS: [(KeyCode, EditorTool); 8] = [
+    (KeyCode::KeyB, EditorTool::Brush),
+    (KeyCode::KeyR, EditorTool::Rectangle),
+    (KeyCode::KeyF, EditorTool::Fill),
+    (KeyCode::KeyL, EditorTool::Line),
+    (KeyCode::KeyT, EditorTool::Stamp),
+    (KeyCode::KeyI, EditorTool::Eyedropper),
+    (KeyCode::KeyM, EditorTool::Select),
+    (KeyCode::KeyC, EditorTool::Collision),
+];
+const COLLISION_KEYS: [(KeyCode, Collision); 4] = [
+    (KeyCode::Digit1, Collision::Full),
+    (KeyCode::Digit2, Collision::Middle),
+    (KeyCode::Digit3, Collision::Slow),
+    (KeyCode::Digit4, Collision::Passable),
+];
+
+#[derive(Resource)]
+pub struct EditorState {
+    pub enabled: bool,
+    pub layer: MapLayer,
+    pub brush: Option<Block>,
+    pub tool: EditorTool,
+    pub stamp: Option<Stamp>,
+    // Button and block where the current rectangle, line, stamp copy or selection drag started.
+    pub drag: Option<(MouseButton, (isize, isize))>,
+    // The stamp follows the cursor as a preview until a left click pastes it.
+    pub pasting: bool,
+    // Button whose click placed or cancelled a paste. It paints nothing until it is released.
+    pub consumed_press: Option<MouseButton>,
+    pub collision: Collision,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        Self { enabled: false, layer: MapLayer::Base, brush: None, tool: EditorTool::Brush, stamp: None, drag: None, pasting: false, consumed_press: None, collision: Collision::Full }
+    }
+}
+
+#[derive(Component)]
+struct EditorStatusText;
+
+#[derive(Component)]
+struct PastePreview;
+
+// Prefab files the palette offers for pasting.
+#[derive(Resource, Default)]
+pub struct PrefabLibrary {
+    pub files: Vec<String>,
+}
+
+// UI panels of the editor. Clicks over them must not paint the map underneath.
+#[derive(Component)]
+pub struct EditorUi;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(EditorState::default())
+            .insert_resource(EditHistory::default())
+            .add_systems(Startup, setup_editor)
+            .add_systems(Update, (
+                toggle_editor,
+                clear_history_on_reload,
+                select_default_brush.run_if(resource_exists::<TilesetRegistry>),
+                (select_tool, track_brush_strokes, paint_hovered_block, use_region_tools, use_selection, clipboard_shortcuts, undo_redo, save_map)
+                    .run_if(editor_enabled.and(resource_exists::<LoadedMap>).and(resource_exists::<TilesetRegistry>)),
+                update_status_text,
+                update_paste_preview.run_if(resource_exists::<TilesetRegistry>),
+            ).chain());
+    }
+}
+
+pub fn editor_enabled(state: Res<EditorState>) -> bool {
+    state.enabled
+}
+
+// Puts a block onto one layer of the map, or erases it with None, and re-autotiles the terrain around
+// it when the painted tile belongs to an autotile rule. Returns every block that changed.
+pub fn paint_block(map: &mut Map, x: isize, y: isize, layer: MapLayer, block: Option<Block>, tilesets: &TilesetManifest) -> Vec<BlockChange> {
+    let rule = block.as_ref()
+        .filter(|_| layer == MapLayer::Base)
+        .and_then(|block| tilesets.autotile_rule_for_tile(&block.texture, block.tile));
+
+    if let (Some(rule), Some(block), Some(current)) = (rule, &block, map.layer_block_at(x, y, layer)) {
+        // Repainting the same terrain would only undo the autotiling of this block.
+        if current.texture == block.texture && rule.contains_tile(current.tile) && current.overrides == block.overrides {
+            return Vec::new();
+        }
+    }
+
+    let touched: Vec<(isize, isize)> = match rule {
+        Some(_) => (y - 1..=y + 1).flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny))).collect(),
+        None => vec![(x, y)],
+    };
+    let before: Vec<Option<Block>> = touched.iter().map(|(bx, by)| map.layer_block_at(*bx, *by, layer).cloned()).collect();
+    let texture = block.as_ref().map(|block| block.texture.clone());
+    if map.set_block(x, y, layer, block).is_none() {
+        return Vec::new();
+    }
+    if let (Some(rule), Some(texture)) = (rule, texture) {
+        map.autotile_around(x, y, &texture, rule);
+    }
+
+    touched.into_iter().zip(before)
+        .filter_map(|((bx, by), before)| {
+            let after = map.layer_block_at(bx, by, layer).cloned();
+            (after != before).then_some(BlockChange { x: bx, y: by, layer, before, after })
+        })
+        .collect()
+}
+
+// Sets or, with None, removes the collision override of a block, keeping its other overrides.
+pub fn set_collision(map: &mut Map, x: isize, y: isize, layer: MapLayer, collision: Option<Collision>) -> Vec<BlockChange> {
+    let Some(block) = map.layer_block_at(x, y, layer) else {
+        return Vec::new();
+    };
+    let overrides = TileOverrides { collision, ..block.overrides.clone() };
+    set_overrides(map, x, y, layer, overrides)
+}
+
+// Replaces the overrides of a block without touching its tile.
+pub fn set_overrides(map: &mut Map, x: isize, y: isize, layer: MapLayer, overrides: TileOverrides) -> Vec<BlockChange> {
+    let Some(before) = map.layer_block_at(x, y, layer).cloned() else {
+        return Vec::new();
+    };
+    if before.overrides == overrides {
+        return Vec::new();
+    }
+    let after = Block { overrides, ..before.clone() };
+    map.set_block(x, y, layer, Some(after.clone()));
+    vec![BlockChange { x, y, layer, before: Some(before), after: Some(after) }]
+}
+
+fn setup_editor(mut commands: Commands) {
+    commands.insert_resource(PrefabLibrary { files: list_prefabs(PREFAB_DIR) });
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 10.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(100.0),
+            left: Val::Px(0.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        EditorStatusText,
+    ));
+}
+
+fn toggle_editor(keyboard_input: Res<ButtonInput<KeyCode>>, mut state: ResMut<EditorState>, mut layer_visibility: ResMut<LayerVisibility>) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        state.enabled = !state.enabled;
+        layer_visibility.editor = state.enabled;
+        info!("Editor {}", if state.enabled { "enabled" } else { "disabled" });
+    }
+}
+
+fn clear_history_on_reload(mut replaced_events: EventReader<MapReplaced>, mut history: ResMut<EditHistory>) {
+    if replaced_events.read().count() > 0 {
+        history.clear();
+    }
+}
+
+fn select_default_brush(mut state: ResMut<EditorState>, tilesets: Res<TilesetRegistry>) {
+    if state.brush.is_some() {
+        return;
+    }
+    let Some(tileset) = tilesets.manifest.tilesets.first() else {
+        return;
+    };
+    let mut rules: Vec<_> = tileset.autotiles.iter().collect();
+    rules.sort_by_key(|(terrain, _)| *terrain);
+    let tile = rules.first().map_or(0, |(_, rule)| rule.default);
+    state.brush = Some(Block { tile, texture: tileset.name.clone(), overrides: TileOverrides::default() });
+}
+
+fn select_tool(keyboard_input: Res<ButtonInput<KeyCode>>, mut state: ResMut<EditorState>) {
+    if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.drag = None;
+        state.pasting = false;
+    }
+    for (key, tool) in TOOL_KEYS {
+        if keyboard_input.just_pressed(key) {
+            state.tool = tool;
+            state.drag = None;
+        }
+    }
+    for (key, collision) in COLLISION_KEYS {
+        if state.tool == EditorTool::Collision && keyboard_input.just_pressed(key) {
+            state.collision = collision;
+        }
+    }
+}
+
+pub fn track_brush_strokes(mouse_input: Res<ButtonInput<MouseButton>>, state: Res<EditorState>, mut history: ResMut<EditHistory>) {
+    if !matches!(state.tool, EditorTool::Brush | EditorTool::Collision) || state.pasting || state.consumed_press.is_some() {
+        return;
+    }
+    if state.tool == EditorTool::Collision && mouse_input.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+        history.begin_stroke(EditKind::Property);
+    } else if mouse_input.just_pressed(MouseButton::Left) {
+        history.begin_stroke(EditKind::Paint);
+    } else if mouse_input.just_pressed(MouseButton::Right) {
+        history.begin_stroke(EditKind::Erase);
+    }
+    if mouse_input.just_released(MouseButton::Left) || mouse_input.just_released(MouseButton::Right) {
+        history.end_stroke();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn paint_hovered_block(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    hovered_block: Res<HoveredBlock>,
+    state: Res<EditorState>,
+    tilesets: Res<TilesetRegistry>,
+    mut loaded_map: ResMut<LoadedMap>,
+    mut changed_events: EventWriter<ChunkChanged>,
+    mut history: ResMut<EditHistory>,
+    q_editor_ui: Query<&RelativeCursorPosition, With<EditorUi>>,
+) {
+    if !matches!(state.tool, EditorTool::Brush | EditorTool::Collision) || state.pasting || state.consumed_press.is_some() || q_editor_ui.iter().any(RelativeCursorPosition::mouse_over) {
+        return;
+    }
+    let (x, y) = (hovered_block.0, hovered_block.1);
+    if state.tool == EditorTool::Collision {
+        // Left paints the selected collision, right removes the override so the tileset default applies.
+        let collision = if mouse_input.pressed(MouseButton::Left) {
+            Some(state.collision)
+        } else if mouse_input.pressed(MouseButton::Right) {
+            None
+        } else {
+            return;
+        };
+        let changes = set_collision(&mut loaded_map.map, x, y, state.layer, collision);
+        changed_events.send_batch(changed_chunks(&loaded_map.map, &changes).into_iter().map(ChunkChanged));
+        history.extend_stroke(changes);
+        return;
+    }
+
+    let block = if mouse_input.pressed(MouseButton::Left) {
+        let Some(brush) = &state.brush else {
+            return;
+        };
+        Some(brush.clone())
+    } else if mouse_input.pressed(MouseButton::Right) {
+        None
+    } else {
+        return;
+    };
+
+    let changes = paint_block(&mut loaded_map.map, x, y, state.layer, block, &tilesets.manifest);
+    changed_events.send_batch(changed_chunks(&loaded_map.map, &changes).into_iter().map(ChunkChanged));
+    history.extend_stroke(changes);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn use_region_tools(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    hovered_block: Res<HoveredBlock>,
+    mut state: ResMut<EditorState>,
+    tilesets: Res<TilesetRegistry>,
+    mut loaded_map: ResMut<LoadedMap>,
+    mut changed_events: EventWriter<ChunkChanged>,
+    mut history: ResMut<EditHistory>,
+    q_editor_ui: Query<&RelativeCursorPosition, With<EditorUi>>,
+) {
+    if state.pasting {
+        return;
+    }
+    let position = (hovered_block.0, hovered_block.1);
+    let (x, y) = position;
+    let layer = state.layer;
+    let over_ui = q_editor_ui.iter().any(RelativeCursorPosition::mouse_over);
+    let pressed = [MouseButton::Left, MouseButton::Right].into_iter().find(|button| mouse_input.just_pressed(*button) && !over_ui);
+    let released = state.drag.filter(|(button, _)| mouse_input.just_released(*button));
+    // Left paints the brush, right erases.
+    let block_for = |button: MouseButton| if button == MouseButton::Left { state.brush.clone() } else { None };
+    let map = &mut loaded_map.map;
+    let manifest = &tilesets.manifest;
+
+    let (kind, changes) = match (state.tool, pressed, released) {
+        (EditorTool::Eyedropper, Some(MouseButton::Left), _) => {
+            if let Some(block) = map.layer_block_at(x, y, layer).cloned() {
+                state.brush = Some(block);
+                state.tool = EditorTool::Brush;
+            }
+            return;
+        }
+        (EditorTool::Fill, Some(button), _) => {
+            if button == MouseButton::Left && state.brush.is_none() {
+                return;
+            }
+            let Some(cells) = flood_fill_cells(map, x, y, layer, FLOOD_FILL_LIMIT) else {
+                warn!("Flood fill refused: the region is larger than {} blocks", FLOOD_FILL_LIMIT);
+                return;
+            };
+            (EditKind::Fill, paint_cells(map, &cells, layer, block_for(button).as_ref(), manifest))
+        }
+        (EditorTool::Stamp, Some(MouseButton::Left), _) => {
+            let Some(stamp) = &state.stamp else {
+                return;
+            };
+            (EditKind::Paint, apply_stamp(map, x, y, layer, stamp, manifest))
+        }
+        (EditorTool::Stamp, _, Some((MouseButton::Right, start))) => {
+            state.stamp = Some(Stamp::copy(map, start, position, layer));
+            state.drag = None;
+            return;
+        }
+        (EditorTool::Rectangle | EditorTool::Line, _, Some((button, start))) => {
+            let cells = if state.tool == EditorTool::Rectangle { rectangle_cells(start, position) } else { line_cells(start, position) };
+            let block = block_for(button);
+            state.drag = None;
+            if button == MouseButton::Left && block.is_none() {
+                return;
+            }
+            let kind = if block.is_some() { EditKind::Fill } else { EditKind::Erase };
+            (kind, paint_cells(map, &cells, layer, block.as_ref(), manifest))
+        }
+        (EditorTool::Rectangle | EditorTool::Line, Some(button), _) | (EditorTool::Stamp, Some(button @ MouseButton::Right), _) => {
+            state.drag = Some((button, position));
+            return;
+        }
+        _ => return,
+    };
+
+    changed_events.send_batch(changed_chunks(map, &changes).into_iter().map(ChunkChanged));
+    history.record(EditCommand { kind, changes });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn use_selection(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    hovered_block: Res<HoveredBlock>,
+    mut state: ResMut<EditorState>,
+    mut selection: ResMut<BlockSelection>,
+    tilesets: Res<TilesetRegistry>,
+    mut loaded_map: ResMut<LoadedMap>,
+    mut changed_events: EventWriter<ChunkChanged>,
+    mut history: ResMut<EditHistory>,
+    q_editor_ui: Query<&RelativeCursorPosition, With<EditorUi>>,
+) {
+    let position = (hovered_block.0, hovered_block.1);
+    let over_ui = q_editor_ui.iter().any(RelativeCursorPosition::mouse_over);
+    let pressed = |button: MouseButton| mouse_input.just_pressed(button) && !over_ui;
+
+    if state.consumed_press.is_some_and(|button| !mouse_input.pressed(button)) {
+        state.consumed_press = None;
+    }
+    if state.pasting {
+        if pressed(MouseButton::Right) {
+            state.pasting = false;
+            state.consumed_press = Some(MouseButton::Right);
+        }
+        if !pressed(MouseButton::Left) {
+            return;
+        }
+        let Some(stamp) = &state.stamp else {
+            return;
+        };
+        let changes = apply_stamp(&mut loaded_map.map, position.0, position.1, state.layer, stamp, &tilesets.manifest);
+        changed_events.send_batch(changed_chunks(&loaded_map.map, &changes).into_iter().map(ChunkChanged));
+        history.record(EditCommand { kind: EditKind::Paint, changes });
+        state.pasting = false;
+        state.consumed_press = Some(MouseButton::Left);
+        return;
+    }
+    if state.tool != EditorTool::Select {
+        return;
+    }
+
+    if pressed(MouseButton::Left) {
+        state.drag = Some((MouseButton::Left, position));
+    } else if pressed(MouseButton::Right) {
+        selection.corners = None;
+    }
+    let Some((_, start)) = state.drag else {
+        return;
+    };
+    if selection.corners != Some((start, position)) {
+        selection.corners = Some((start, position));
+    }
+    if mouse_input.just_released(MouseButton::Left) {
+        state.drag = None;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn clipboard_shortcuts(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    selection: Res<BlockSelection>,
+    mut state: ResMut<EditorState>,
+    tilesets: Res<TilesetRegistry>,
+    mut loaded_map: ResMut<LoadedMap>,
+    mut changed_events: EventWriter<ChunkChanged>,
+    mut history: ResMut<EditHistory>,
+    mut prefabs: ResMut<PrefabLibrary>,
+) {
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyV) && state.stamp.is_some() {
+        state.pasting = true;
+        return;
+    }
+
+    let (copy, cut, save) = (
+        keyboard_input.just_pressed(KeyCode::KeyC),
+        keyboard_input.just_pressed(KeyCode::KeyX),
+        keyboard_input.just_pressed(KeyCode::KeyP),
+    );
+    let Some((min, max)) = selection.bounds().filter(|_| copy || cut || save) else {
+        return;
+    };
+    let layer = state.layer;
+    let stamp = Stamp::copy(&loaded_map.map, min, max, layer);
+
+    if save {
+        match save_prefab(&stamp, PREFAB_DIR) {
+            Ok(path) => {
+                info!("Saved prefab {}", path);
+                prefabs.files.push(path);
+            }
+            Err(error) => error!("Failed to save prefab: {}", error),
+        }
+    }
+    if cut {
+        if layer == MapLayer::Base {
+            warn!("Base blocks can only be replaced, the selection was copied instead of cut");
+        } else {
+            let changes = paint_cells(&mut loaded_map.map, &rectangle_cells(min, max), layer, None, &tilesets.manifest);
+            changed_events.send_batch(changed_chunks(&loaded_map.map, &changes).into_iter().map(ChunkChanged));
+            history.record(EditCommand { kind: EditKind::Erase, changes });
+        }
+    }
+    state.stamp = Some(stamp);
+}
+
+// Draws the stamp being pasted translucently, with its top-left block under the cursor.
+fn update_paste_preview(
+    mut commands: Commands,
+    state: Res<EditorState>,
+    hovered_block: Res<HoveredBlock>,
+    tilesets: Res<TilesetRegistry>,
+    resolution: Res<Resolution>,
+    mut q_preview: Query<(Entity, &mut Transform), With<PastePreview>>,
+) {
+    let (x, y) = (hovered_block.0, hovered_block.1);
+    let origin = coords::block_to_world(IVec2::new(x as i32, y as i32), &resolution);
+
+    if !state.is_changed() {
+        for (_, mut transform) in q_preview.iter_mut() {
+            transform.translation = origin.extend(transform.translation.z);
+        }
+        return;
+    }
+    for (entity, _) in q_preview.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(stamp) = state.stamp.as_ref().filter(|_| state.enabled && state.pasting) else {
+        return;
+    };
+
+    commands.spawn((
+        Transform::from_translation(origin.extend(98.0)),
+        Visibility::default(),
+        PastePreview,
+    )).with_children(|parent| {
+        for (i, row) in stamp.blocks.iter().enumerate() {
+            for (j, block) in row.iter().enumerate() {
+                let Some(tileset) = block.as_ref().and_then(|block| tilesets.get(&block.texture)) else {
+                    continue;
+                };
+                let mut sprite = Sprite::from_atlas_image(
+                    tileset.image.clone(),
+                    TextureAtlas {
+                        layout: tileset.layout.clone(),
+                        index: block.as_ref().map_or(0, |block| block.tile),
+                    },
+                );
+                sprite.color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+                parent.spawn((
+                    sprite,
+                    Transform::from_scale(Vec3::new(resolution.pixel_ratio as f32, resolution.pixel_ratio as f32, 1.0))
+                        .with_translation(coords::block_to_world(IVec2::new(j as i32, i as i32), &resolution).extend(0.0)),
+                    RenderLayers::layer(EDITOR_LAYER),
+                ));
+            }
+        }
+    });
+}
+
+fn undo_redo(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<EditHistory>,
+    mut loaded_map: ResMut<LoadedMap>,
+    mut changed_events: EventWriter<ChunkChanged>,
+) {
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let chunks = if keyboard_input.just_pressed(KeyCode::KeyY) || (shift && keyboard_input.just_pressed(KeyCode::KeyZ)) {
+        history.redo(&mut loaded_map.map)
+    } else if keyboard_input.just_pressed(KeyCode::KeyZ) {
+        history.undo(&mut loaded_map.map)
+    } else {
+        return;
+    };
+    changed_events.send_batch(chunks.unwrap_or_default().into_iter().map(ChunkChanged));
+}
+
+fn save_map(keyboard_input: Res<ButtonInput<KeyCode>>, loaded_map: Res<LoadedMap>) {
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !ctrl || !keyboard_input.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+
+    let path = format!("assets/{}", MAP_PATH);
+    match loaded_map.map.save_to_json(&path) {
+        Ok(()) => info!("Saved map to {}", path),
+        Err(error) => error!("Failed to save map to {}: {}", path, error),
+    }
+}
+
+fn update_status_text(
+    state: Res<EditorState>,
+    mut q_text: Query<(&mut Text, &mut Visibility), With<EditorStatusText>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in q_text.iter_mut() {
+        *visibility = if state.enabled { Visibility::Inherited } else { Visibility::Hidden };
+        let brush = state.brush.as_ref().map_or(String::from("none"), |brush| format!("{} #{}", brush.texture, brush.tile));
+        let mut status = format!("EDITOR ({:?}): {:?} tool, layer {:?}, brush {}", TOGGLE_KEY, state.tool, state.layer, brush);
+        if let Some(stamp) = &state.stamp {
+            status += &format!(", stamp {}x{}", stamp.width(), stamp.height());
+        }
+        if let Some((_, (x, y))) = state.drag {
+            status += &format!(", dragging from {} {}", x, y);
+        }
+        if state.tool == EditorTool::Collision {
+            status += &format!(", collision {:?}", state.collision);
+        }
+        if state.pasting {
+            status += ", pasting (left click to place, right click to cancel)";
+        }
+        **text = status;
+    }
+}
@@ -1,7 +1,6 @@
 use bevy::prelude::*;
-use bevy::render::view::{RenderLayers};
-use crate::{cursor, gridselector, map, player, resolution};
-use crate::layers::{CURSOR_LAYER, EDITOR_LAYER, MAP_LAYER, PLAYER_LAYER};
+use crate::{collision_overlay, cursor, editor, gridselector, layers, map, palette, player, resolution, tileset};
+use crate::layers::LayerVisibility;
 pub struct GamePlugin;
 
 #[derive(Component)]
@@ -16,11 +15,16 @@ impl Plugin for GamePlugin {
                 gridselector::GridSelectorPlugin,
                 resolution::ResolutionPlugin,
                 cursor::CursorPlugin,
+                tileset::TilesetPlugin,
+                editor::EditorPlugin,
+                palette::PalettePlugin,
+                collision_overlay::CollisionOverlayPlugin,
+                layers::LayersPlugin,
             )
         ).add_systems(Startup, setup_scene);
     }
 }
 
-fn setup_scene(mut commands: Commands) {
-    commands.spawn((Camera2d::default(), MainCamera, RenderLayers::from_layers(&[MAP_LAYER,PLAYER_LAYER, CURSOR_LAYER, EDITOR_LAYER])));
+fn setup_scene(mut commands: Commands, visibility: Res<LayerVisibility>) {
+    commands.spawn((Camera2d, MainCamera, visibility.render_layers()));
 }
\ No newline at end of file
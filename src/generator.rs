@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use bevy::reflect::erased_serde::__private::serde::{Deserialize, Serialize};
+use crate::autotile::AutotileRule;
+use crate::map::{Block, Chunk, Map};
+use crate::tileset::{TileOverrides, TilesetManifest};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Biome {
+    pub texture: String,
+    pub terrain: String,
+    pub weight: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GeneratorParams {
+    pub seed: u64,
+    pub size_in_chunks: [usize; 2],
+    pub chunk_size: usize,
+    pub biomes: Vec<Biome>,
+    pub noise_scale: f32,
+    #[serde(default = "default_decoration_chance")]
+    pub decoration_chance: f64,
+}
+
+fn default_decoration_chance() -> f64 {
+    0.1
+}
+
+#[derive(Debug)]
+pub enum GeneratorError {
+    NoBiomes,
+    UnknownAutotile { texture: String, terrain: String },
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratorError::NoBiomes => write!(f, "at least one biome with a positive weight is required"),
+            GeneratorError::UnknownAutotile { texture, terrain } => {
+                write!(f, "tileset \"{}\" has no autotile rule for terrain \"{}\"", texture, terrain)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {}
+
+pub fn generate(params: &GeneratorParams, tilesets: &TilesetManifest) -> Result<Map, GeneratorError> {
+    let total_weight: f32 = params.biomes.iter().map(|biome| biome.weight.max(0.0)).sum();
+    if total_weight <= 0.0 {
+        return Err(GeneratorError::NoBiomes);
+    }
+    let rules = params.biomes.iter().map(|biome| {
+        tilesets.autotile_rule(&biome.texture, &biome.terrain).ok_or_else(|| GeneratorError::UnknownAutotile {
+            texture: biome.texture.clone(),
+            terrain: biome.terrain.clone(),
+        })
+    }).collect::<Result<Vec<&AutotileRule>, _>>()?;
+
+    let width = params.size_in_chunks[0] * params.chunk_size;
+    let height = params.size_in_chunks[1] * params.chunk_size;
+    let biome_grid: Vec<Vec<usize>> = (0..height).map(|y| {
+        (0..width).map(|x| {
+            let noise = value_noise(params.seed, x as f32 / params.noise_scale, y as f32 / params.noise_scale);
+            pick_biome(&params.biomes, noise * total_weight)
+        }).collect()
+    }).collect();
+
+    let mut tiles = vec![vec![(0, 0); width]; height];
+    for (index, rule) in rules.iter().enumerate() {
+        let region: Vec<Vec<bool>> = biome_grid.iter().map(|row| row.iter().map(|biome| *biome == index).collect()).collect();
+        for (y, row) in rule.autotile_region(&region).into_iter().enumerate() {
+            for (x, tile) in row.into_iter().enumerate() {
+                if let Some(tile) = tile {
+                    tiles[y][x] = (index, tile);
+                }
+            }
+        }
+    }
+
+    let mut rng = SplitMix64(params.seed);
+    let mut chunks = vec![vec![]; params.size_in_chunks[1]];
+    for (chunk_y, chunk_row) in chunks.iter_mut().enumerate() {
+        for chunk_x in 0..params.size_in_chunks[0] {
+            let blocks = (0..params.chunk_size).map(|i| {
+                (0..params.chunk_size).map(|j| {
+                    let (biome, tile) = tiles[chunk_y * params.chunk_size + i][chunk_x * params.chunk_size + j];
+                    Block {
+                        tile: decorate(rules[biome], tile, params.decoration_chance, &mut rng),
+                        texture: params.biomes[biome].texture.clone(),
+                        overrides: TileOverrides::default(),
+                    }
+                }).collect()
+            }).collect();
+            chunk_row.push(Chunk { blocks, layers: BTreeMap::new() });
+        }
+    }
+
+    Ok(Map { chunk_size: params.chunk_size, chunks })
+}
+
+fn pick_biome(biomes: &[Biome], mut value: f32) -> usize {
+    for (index, biome) in biomes.iter().enumerate() {
+        let weight = biome.weight.max(0.0);
+        if value < weight {
+            return index;
+        }
+        value -= weight;
+    }
+    biomes.iter().rposition(|biome| biome.weight > 0.0).unwrap_or(0)
+}
+
+fn decorate(rule: &AutotileRule, tile: usize, chance: f64, rng: &mut SplitMix64) -> usize {
+    // Always draw from the rng so the decoration of one block never shifts the rest of the map.
+    let roll = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    let variant = rng.next_u64();
+    if tile == rule.tile_for_mask(rule.full_mask()) && !rule.variants.is_empty() && roll < chance {
+        return rule.variants[(variant % rule.variants.len() as u64) as usize];
+    }
+    tile
+}
+
+// SplitMix64 is used for both the noise and the decorations, so generated maps are stable across
+// platforms and rand versions.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.0)
+    }
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn lattice_value(seed: u64, x: i64, y: i64) -> f32 {
+    let z = mix(seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F));
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let (ix, iy) = (x0 as i64, y0 as i64);
+
+    let top = lattice_value(seed, ix, iy) * (1.0 - tx) + lattice_value(seed, ix + 1, iy) * tx;
+    let bottom = lattice_value(seed, ix, iy + 1) * (1.0 - tx) + lattice_value(seed, ix + 1, iy + 1) * tx;
+    (top * (1.0 - ty) + bottom * ty).min(0.999_999)
+}
@@ -1,8 +1,10 @@
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 use bevy::window::PrimaryWindow;
+use crate::coords;
 use crate::game::MainCamera;
 use crate::layers::{EDITOR_LAYER};
+use crate::map::MapLayer;
 use crate::resolution;
 
 pub struct GridSelectorPlugin;
@@ -12,15 +14,33 @@ pub struct GridSelectorPlugin;
 struct MousePosition;
 
 #[derive(Component)]
-struct BlockHovered(isize, isize);
+struct BlockHovered;
 
 #[derive(Component)]
 struct GridSelector;
 
 #[derive(Component)]
 struct WorldPosition;
+// Block under the mouse as a map position: column, then row growing downwards.
 #[derive(Resource, Default)]
-struct HoveredBlock(isize, isize);
+pub struct HoveredBlock(pub isize, pub isize);
+
+// Rectangle of blocks picked by dragging, as two opposite corners in map block positions.
+#[derive(Resource, Default)]
+pub struct BlockSelection {
+    pub corners: Option<((isize, isize), (isize, isize))>,
+}
+
+impl BlockSelection {
+    // Top-left and bottom-right corners.
+    pub fn bounds(&self) -> Option<((isize, isize), (isize, isize))> {
+        let (a, b) = self.corners?;
+        Some(((a.0.min(b.0), a.1.min(b.1)), (a.0.max(b.0), a.1.max(b.1))))
+    }
+}
+
+#[derive(Component)]
+struct SelectionOutline;
 
 #[derive(Resource, Default)]
 struct MyWorldCoords(Vec2);
@@ -30,8 +50,9 @@ impl Plugin for GridSelectorPlugin {
         app
             .insert_resource(MyWorldCoords::default())
             .insert_resource(HoveredBlock::default())
+            .insert_resource(BlockSelection::default())
             .add_systems(Startup, (setup, position_selector))
-            .add_systems(Update, (cursor_position, get_hovered_block, update_text, update_selector_position, update_world_position));
+            .add_systems(Update, (cursor_position, get_hovered_block, update_text, update_selector_position, update_world_position, update_selection_outline));
     }
 }
 
@@ -99,7 +120,7 @@ fn setup(
             font_size: 10.0,
             ..default()
         },
-        BlockHovered(0, 0)
+        BlockHovered
     ));
 }
 
@@ -120,14 +141,28 @@ fn position_selector(
         Sprite::from_atlas_image(
             texture.clone(),
             TextureAtlas {
-                layout: texture_atlas_layout,
+                layout: texture_atlas_layout.clone(),
                 index: 0,
             },
         ),
-        Transform::from_scale(Vec3::new(resolution.pixel_ratio as f32, resolution.pixel_ratio as f32, 1.0)).with_translation(Vec3::new(0.0, 0.0, 100.0)),
+        Transform::from_scale(Vec3::new(resolution.pixel_ratio as f32, resolution.pixel_ratio as f32, 1.0)).with_translation(Vec3::new(0.0, 0.0, MapLayer::Weather.z() + 2.0)),
         RenderLayers::layer(EDITOR_LAYER),
         GridSelector
     ));
+
+    commands.spawn((
+        Sprite::from_atlas_image(
+            texture,
+            TextureAtlas {
+                layout: texture_atlas_layout,
+                index: 0,
+            },
+        ),
+        Transform::from_translation(Vec3::new(0.0, 0.0, MapLayer::Weather.z() + 1.0)),
+        Visibility::Hidden,
+        RenderLayers::layer(EDITOR_LAYER),
+        SelectionOutline
+    ));
 }
 
 fn cursor_position(
@@ -136,21 +171,11 @@ fn cursor_position(
 ) {
     if let Some(position) = q_windows.single().cursor_position() {
         for mut span in text_query.iter_mut() {
-            **span = format!("x: {:?} y: {:?}", position.x, position.y).into();
+            **span = format!("x: {:?} y: {:?}", position.x, position.y)
         }
     }
 }
 
-fn calculate_value(world_position: f32) -> f32 {
-    let position_correction = 32.0;
-    let corrected_position = world_position.abs() - position_correction;
-    let multiplier = if corrected_position != 0.0 {
-        (corrected_position / 32.0).ceil() as i32 - 1
-    } else {
-        0
-    };
-    (multiplier as f32 / 2.0).ceil() * world_position.signum()
-}
 fn get_hovered_block(
     mut mycoords: ResMut<MyWorldCoords>,
     q_window: Query<&Window, With<PrimaryWindow>>,
@@ -163,15 +188,12 @@ fn get_hovered_block(
     let window = q_window.single();
 
     if let Some(cursor) = window.cursor_position() {
-        if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) {
-            let world_position = ray.origin.truncate();
+        if let Some(world_position) = coords::screen_to_world(camera, camera_transform, cursor) {
             mycoords.0 = world_position;
-            let _block_size = (resolution.pixel_ratio as f32 + 1.0) * resolution.frame_size.x as f32;
-            let grid_x = ((world_position.x) / (64.0 / 2.0) ) as isize;
-            let grid_y = ((world_position.y) / (64.0 / 2.0)) as isize;
+            let block = coords::world_to_block(world_position, &resolution);
 
-            hovered_block.0 = (grid_x as f32 - calculate_value(world_position.x)) as isize;
-            hovered_block.1 = (grid_y as f32 - calculate_value(world_position.y)) as isize;
+            hovered_block.0 = block.x as isize;
+            hovered_block.1 = block.y as isize;
         }
     }
 }
@@ -180,7 +202,7 @@ fn update_text(
     mut text_query: Query<&mut TextSpan, With<BlockHovered>>,
 ) {
     for mut span in text_query.iter_mut() {
-        **span = format!("x: {:?} y: {:?}", hovered_block.0, hovered_block.1).into();
+        **span = format!("x: {:?} y: {:?}", hovered_block.0, hovered_block.1)
     }
 }
 
@@ -189,9 +211,9 @@ fn update_selector_position(
     mut q_selector: Query<(&mut Transform, &RenderLayers), With<GridSelector>>,
     resolution: Res<resolution::Resolution>,
 ) {
+    let position = coords::block_to_world(IVec2::new(hovered_block.0 as i32, hovered_block.1 as i32), &resolution);
     for (mut transform, _) in q_selector.iter_mut() {
-        transform.translation.x = hovered_block.0 as f32 * resolution.frame_size.x as f32 * resolution.pixel_ratio as f32;
-        transform.translation.y = hovered_block.1 as f32 * resolution.frame_size.x as f32 * resolution.pixel_ratio as f32;
+        transform.translation = position.extend(transform.translation.z);
     }
 }
 
@@ -200,6 +222,29 @@ fn update_world_position(
     mut text_query: Query<&mut TextSpan, With<WorldPosition>>,
 ) {
     for mut span in text_query.iter_mut() {
-        **span = format!("x: {:?} y: {:?}", mycoords.0.x, mycoords.0.y).into();
+        **span = format!("x: {:?} y: {:?}", mycoords.0.x, mycoords.0.y)
     }
-}
\ No newline at end of file
+}
+
+// Stretches the selector sprite over the whole selection.
+fn update_selection_outline(
+    selection: Res<BlockSelection>,
+    mut q_outline: Query<(&mut Transform, &mut Visibility), With<SelectionOutline>>,
+    resolution: Res<resolution::Resolution>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    for (mut transform, mut visibility) in q_outline.iter_mut() {
+        let Some((min, max)) = selection.bounds() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let (min, max) = (IVec2::new(min.0 as i32, min.1 as i32), IVec2::new(max.0 as i32, max.1 as i32));
+        let center = (coords::block_to_world(min, &resolution) + coords::block_to_world(max, &resolution)) / 2.0;
+        let size = (max - min + IVec2::ONE).as_vec2() * resolution.pixel_ratio as f32;
+        transform.translation = center.extend(transform.translation.z);
+        transform.scale = size.extend(1.0);
+        *visibility = Visibility::Inherited;
+    }
+}
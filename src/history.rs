@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use crate::map::{Block, Map, MapLayer};
+
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockChange {
+    pub x: isize,
+    pub y: isize,
+    pub layer: MapLayer,
+    pub before: Option<Block>,
+    pub after: Option<Block>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditKind {
+    Paint,
+    Erase,
+    Fill,
+    Property,
+}
+
+// One undo step. Changes are applied in order and reverted in reverse order, so a block touched twice
+// ends up at its first `before`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditCommand {
+    pub kind: EditKind,
+    pub changes: Vec<BlockChange>,
+}
+
+impl EditCommand {
+    pub fn apply(&self, map: &mut Map) -> Vec<IVec2> {
+        for change in &self.changes {
+            map.set_block(change.x, change.y, change.layer, change.after.clone());
+        }
+        changed_chunks(map, &self.changes)
+    }
+
+    pub fn revert(&self, map: &mut Map) -> Vec<IVec2> {
+        for change in self.changes.iter().rev() {
+            map.set_block(change.x, change.y, change.layer, change.before.clone());
+        }
+        changed_chunks(map, &self.changes)
+    }
+}
+
+pub fn changed_chunks(map: &Map, changes: &[BlockChange]) -> Vec<IVec2> {
+    let mut chunks = Vec::new();
+    for chunk in changes.iter().filter_map(|change| map.chunk_of(change.x, change.y)) {
+        if !chunks.contains(&chunk) {
+            chunks.push(chunk);
+        }
+    }
+    chunks
+}
+
+// Undo and redo stacks of map edits. A brush stroke stays open while the mouse is held and becomes a
+// single undo step when it ends.
+#[derive(Resource)]
+pub struct EditHistory {
+    undo: VecDeque<EditCommand>,
+    redo: Vec<EditCommand>,
+    stroke: Option<EditCommand>,
+    limit: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl EditHistory {
+    pub fn new(limit: usize) -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new(), stroke: None, limit }
+    }
+
+    pub fn record(&mut self, command: EditCommand) {
+        self.end_stroke();
+        self.push(command);
+    }
+
+    pub fn begin_stroke(&mut self, kind: EditKind) {
+        self.end_stroke();
+        self.stroke = Some(EditCommand { kind, changes: Vec::new() });
+    }
+
+    pub fn extend_stroke(&mut self, changes: Vec<BlockChange>) {
+        match &mut self.stroke {
+            Some(stroke) => stroke.changes.extend(changes),
+            None => self.push(EditCommand { kind: EditKind::Paint, changes }),
+        }
+    }
+
+    pub fn end_stroke(&mut self) {
+        if let Some(stroke) = self.stroke.take() {
+            self.push(stroke);
+        }
+    }
+
+    pub fn undo(&mut self, map: &mut Map) -> Option<Vec<IVec2>> {
+        self.end_stroke();
+        let command = self.undo.pop_back()?;
+        let chunks = command.revert(map);
+        self.redo.push(command);
+        Some(chunks)
+    }
+
+    pub fn redo(&mut self, map: &mut Map) -> Option<Vec<IVec2>> {
+        self.end_stroke();
+        let command = self.redo.pop()?;
+        let chunks = command.apply(map);
+        self.undo.push_back(command);
+        Some(chunks)
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.limit);
+    }
+
+    fn push(&mut self, command: EditCommand) {
+        if command.changes.is_empty() || self.limit == 0 {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(command);
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
@@ -1,7 +1,49 @@
 // src/layers.rs
-use bevy::render::view::Layer;
+use bevy::prelude::*;
+use bevy::render::view::{Layer, RenderLayers};
+use crate::game::MainCamera;
 
 pub const CURSOR_LAYER: Layer = 0;
-pub const EDITOR_LAYER: Layer = 0;
 pub const PLAYER_LAYER: Layer = 1;
 pub const MAP_LAYER: Layer = 2;
+pub const EDITOR_LAYER: Layer = 3;
+
+pub struct LayersPlugin;
+
+// Which render layers the main camera draws. Editor gizmos start hidden and follow the editor toggle.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct LayerVisibility {
+    pub map: bool,
+    pub player: bool,
+    pub editor: bool,
+    pub cursor: bool,
+}
+
+impl Default for LayerVisibility {
+    fn default() -> Self {
+        Self { map: true, player: true, editor: false, cursor: true }
+    }
+}
+
+impl LayerVisibility {
+    pub fn render_layers(&self) -> RenderLayers {
+        let layers = [(MAP_LAYER, self.map), (PLAYER_LAYER, self.player), (EDITOR_LAYER, self.editor), (CURSOR_LAYER, self.cursor)];
+        let visible: Vec<Layer> = layers.into_iter().filter(|(_, visible)| *visible).map(|(layer, _)| layer).collect();
+
+        RenderLayers::from_layers(&visible)
+    }
+}
+
+impl Plugin for LayersPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LayerVisibility>()
+            .add_systems(Update, update_camera_layers.run_if(resource_changed::<LayerVisibility>));
+    }
+}
+
+fn update_camera_layers(visibility: Res<LayerVisibility>, mut q_camera: Query<&mut RenderLayers, With<MainCamera>>) {
+    for mut render_layers in q_camera.iter_mut() {
+        *render_layers = visibility.render_layers();
+    }
+}
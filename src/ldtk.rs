@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use bevy::reflect::erased_serde::__private::serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::map::{Block, Collision, Map, MapError, MapLayer};
+use crate::tileset::TileOverrides;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkProject {
+    #[serde(rename = "jsonVersion", default)]
+    pub json_version: String,
+    #[serde(rename = "externalLevels", default)]
+    pub external_levels: bool,
+    pub defs: LdtkDefinitions,
+    pub levels: Vec<LdtkLevel>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkDefinitions {
+    #[serde(default)]
+    pub layers: Vec<LdtkLayerDefinition>,
+    #[serde(default)]
+    pub tilesets: Vec<LdtkTilesetDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkLayerDefinition {
+    pub uid: i64,
+    pub identifier: String,
+    #[serde(rename = "intGridValues", default)]
+    pub int_grid_values: Vec<LdtkIntGridValue>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkIntGridValue {
+    pub value: i64,
+    #[serde(default)]
+    pub identifier: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkTilesetDefinition {
+    pub uid: i64,
+    pub identifier: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkLevel {
+    pub identifier: String,
+    #[serde(rename = "layerInstances", default)]
+    pub layer_instances: Option<Vec<LdtkLayerInstance>>,
+    #[serde(rename = "fieldInstances", default)]
+    pub field_instances: Vec<LdtkField>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkLayerInstance {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    #[serde(rename = "__type")]
+    pub kind: String,
+    #[serde(rename = "__cWid")]
+    pub width: usize,
+    #[serde(rename = "__cHei")]
+    pub height: usize,
+    #[serde(rename = "__gridSize")]
+    pub grid_size: i64,
+    #[serde(rename = "__tilesetDefUid", default)]
+    pub tileset_uid: Option<i64>,
+    #[serde(rename = "layerDefUid")]
+    pub layer_uid: i64,
+    #[serde(rename = "intGridCsv", default)]
+    pub int_grid: Vec<i64>,
+    #[serde(rename = "gridTiles", default)]
+    pub grid_tiles: Vec<LdtkTile>,
+    #[serde(rename = "autoLayerTiles", default)]
+    pub auto_layer_tiles: Vec<LdtkTile>,
+    #[serde(rename = "entityInstances", default)]
+    pub entities: Vec<LdtkEntity>,
+    #[serde(rename = "pxOffsetX", default)]
+    pub offset_x: i64,
+    #[serde(rename = "pxOffsetY", default)]
+    pub offset_y: i64,
+    #[serde(rename = "__opacity", default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkTile {
+    pub px: [i64; 2],
+    pub t: usize,
+    #[serde(default)]
+    pub f: u8,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkEntity {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    #[serde(rename = "__grid")]
+    pub grid: [i64; 2],
+    #[serde(rename = "fieldInstances", default)]
+    pub field_instances: Vec<LdtkField>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdtkField {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    #[serde(rename = "__value")]
+    pub value: Value,
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EntitySpawn {
+    pub identifier: String,
+    pub layer: String,
+    pub block: [i64; 2],
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+}
+
+pub struct LdtkImportOptions {
+    pub chunk_size: usize,
+    pub level: Option<String>,
+    // Used for Base cells that no tile layer mapped onto Base covers.
+    pub fill: Block,
+}
+
+pub struct LdtkImport {
+    pub map: Map,
+    pub entities: Vec<EntitySpawn>,
+    pub warnings: Vec<String>,
+}
+
+pub fn import_ldtk_file(path: &str, options: &LdtkImportOptions) -> Result<LdtkImport, MapError> {
+    import_ldtk_json(&std::fs::read_to_string(path)?, options)
+}
+
+pub fn import_ldtk_json(contents: &str, options: &LdtkImportOptions) -> Result<LdtkImport, MapError> {
+    let project: LdtkProject = serde_json::from_str(contents)?;
+    import_ldtk(&project, options)
+}
+
+pub fn import_ldtk(project: &LdtkProject, options: &LdtkImportOptions) -> Result<LdtkImport, MapError> {
+    if project.external_levels {
+        return Err(MapError::Import(String::from("LDtk projects with separate level files are not supported")));
+    }
+    if options.chunk_size == 0 {
+        return Err(MapError::Import(String::from("chunk size must be greater than zero")));
+    }
+    let level = match &options.level {
+        Some(name) => project.levels.iter().find(|level| &level.identifier == name)
+            .ok_or_else(|| MapError::Import(format!("level \"{}\" not found", name)))?,
+        None => project.levels.first().ok_or_else(|| MapError::Import(String::from("project has no levels")))?,
+    };
+    let Some(instances) = &level.layer_instances else {
+        return Err(MapError::Import(format!("level \"{}\" has no layer data", level.identifier)));
+    };
+
+    let mut warnings = Vec::new();
+    if !level.field_instances.is_empty() {
+        warnings.push(format!("fields of level \"{}\" are not imported", level.identifier));
+    }
+
+    // LDtk lists layers from top to bottom, so the bottom layer defines the grid.
+    let Some(grid_size) = instances.last().map(|instance| instance.grid_size) else {
+        return Ok(LdtkImport { map: Map::from_layer_grids(options.chunk_size, 0, 0, &BTreeMap::new(), &options.fill), entities: Vec::new(), warnings });
+    };
+    if let Some(instance) = instances.iter().find(|instance| instance.grid_size <= 0) {
+        return Err(MapError::Import(format!("layer \"{}\" has a grid size of {}px", instance.identifier, instance.grid_size)));
+    }
+    if let Some(instance) = instances.iter().find(|instance| instance.width == 0 && !instance.int_grid.is_empty()) {
+        return Err(MapError::Import(format!("layer \"{}\" has IntGrid values but no width", instance.identifier)));
+    }
+    let width = instances.iter().filter(|i| i.grid_size == grid_size).map(|i| i.width).max().unwrap_or(0);
+    let height = instances.iter().filter(|i| i.grid_size == grid_size).map(|i| i.height).max().unwrap_or(0);
+
+    let mut grids: BTreeMap<MapLayer, Vec<Vec<Option<Block>>>> = BTreeMap::new();
+    let mut collisions: Vec<Vec<Option<Collision>>> = vec![vec![None; width]; height];
+    let mut entities = Vec::new();
+
+    for instance in instances.iter().rev() {
+        if instance.grid_size != grid_size {
+            warnings.push(format!("layer \"{}\" uses a {}px grid instead of {}px and was skipped", instance.identifier, instance.grid_size, grid_size));
+            continue;
+        }
+        if instance.offset_x != 0 || instance.offset_y != 0 {
+            warnings.push(format!("offset of layer \"{}\" is ignored", instance.identifier));
+        }
+        if instance.opacity != 1.0 || !instance.visible {
+            warnings.push(format!("opacity and visibility of layer \"{}\" are ignored", instance.identifier));
+        }
+
+        match instance.kind.as_str() {
+            "IntGrid" => read_int_grid(project, instance, &mut collisions, &mut warnings),
+            "Entities" => entities.extend(instance.entities.iter().map(|entity| EntitySpawn {
+                identifier: entity.identifier.clone(),
+                layer: instance.identifier.clone(),
+                block: entity.grid,
+                fields: entity.field_instances.iter().map(|field| (field.identifier.clone(), field.value.clone())).collect(),
+            })),
+            "Tiles" | "AutoLayer" => {}
+            kind => warnings.push(format!("{} layer \"{}\" is not supported and was skipped", kind, instance.identifier)),
+        }
+
+        let tiles: Vec<&LdtkTile> = instance.grid_tiles.iter().chain(&instance.auto_layer_tiles).collect();
+        if tiles.is_empty() {
+            continue;
+        }
+        let Some(map_layer) = MapLayer::from_name(&instance.identifier) else {
+            warnings.push(format!("tiles of layer \"{}\" do not match a map layer and were skipped", instance.identifier));
+            continue;
+        };
+        let Some(tileset) = project.defs.tilesets.iter().find(|tileset| Some(tileset.uid) == instance.tileset_uid) else {
+            return Err(MapError::Import(format!("layer \"{}\" has tiles but no tileset", instance.identifier)));
+        };
+        if grids.contains_key(&map_layer) {
+            warnings.push(format!("layer \"{}\" is merged into the {:?} layer", instance.identifier, map_layer));
+        }
+
+        // LDtk identifiers are capitalised, tileset names in the manifest are not.
+        let texture = tileset.identifier.to_lowercase();
+        let grid = grids.entry(map_layer).or_insert_with(|| vec![vec![None; width]; height]);
+        let (mut flipped, mut stacked) = (0, 0);
+        for tile in tiles {
+            if tile.px[0] < 0 || tile.px[1] < 0 {
+                continue;
+            }
+            let (x, y) = ((tile.px[0] / grid_size) as usize, (tile.px[1] / grid_size) as usize);
+            let Some(cell) = grid.get_mut(y).and_then(|row| row.get_mut(x)) else {
+                continue;
+            };
+            flipped += usize::from(tile.f != 0);
+            stacked += usize::from(cell.is_some());
+            *cell = Some(Block { tile: tile.t, texture: texture.clone(), overrides: TileOverrides::default() });
+        }
+        if flipped > 0 {
+            warnings.push(format!("{} flipped tiles in layer \"{}\" were imported unflipped", flipped, instance.identifier));
+        }
+        if stacked > 0 {
+            warnings.push(format!("{} stacked tiles in layer \"{}\" were replaced by the topmost tile", stacked, instance.identifier));
+        }
+    }
+
+    let empty_base = grids.get(&MapLayer::Base)
+        .map_or(width * height, |base| base.iter().flatten().filter(|cell| cell.is_none()).count());
+    if empty_base > 0 {
+        warnings.push(format!("{} empty Base cells were filled with tile {} of \"{}\"", empty_base, options.fill.tile, options.fill.texture));
+    }
+
+    let mut map = Map::from_layer_grids(options.chunk_size, width, height, &grids, &options.fill);
+    for (y, row) in collisions.iter().enumerate() {
+        for (x, collision) in row.iter().enumerate() {
+            if let (Some(collision), Some(block)) = (collision, map.block_at_mut(x as isize, y as isize)) {
+                block.overrides.collision = Some(*collision);
+            }
+        }
+    }
+
+    Ok(LdtkImport { map, entities, warnings })
+}
+
+fn read_int_grid(project: &LdtkProject, instance: &LdtkLayerInstance, collisions: &mut [Vec<Option<Collision>>], warnings: &mut Vec<String>) {
+    let values = project.defs.layers.iter()
+        .find(|layer| layer.uid == instance.layer_uid)
+        .map(|layer| layer.int_grid_values.as_slice())
+        .unwrap_or_default();
+    let collision_for = |value: i64| {
+        values.iter().find(|v| v.value == value).and_then(|v| v.identifier.as_deref()).and_then(Collision::from_name)
+    };
+
+    let mut unknown = BTreeMap::new();
+    for (index, value) in instance.int_grid.iter().enumerate() {
+        if *value == 0 {
+            continue;
+        }
+        let (x, y) = (index % instance.width, index / instance.width);
+        match (collision_for(*value), collisions.get_mut(y).and_then(|row| row.get_mut(x))) {
+            (Some(collision), Some(cell)) => *cell = Some(cell.map_or(collision, |current| current.most_restrictive(collision))),
+            (None, _) => *unknown.entry(*value).or_insert(0) += 1,
+            _ => {}
+        }
+    }
+    for (value, count) in unknown {
+        warnings.push(format!("{} cells of IntGrid value {} in layer \"{}\" do not name a collision and were skipped", count, value, instance.identifier));
+    }
+}
@@ -0,0 +1,24 @@
+pub mod player;
+pub mod autotile;
+pub mod binary_map;
+pub mod game;
+pub mod generator;
+pub mod map;
+pub mod map_loader;
+pub mod map_render;
+pub mod map_stats;
+pub mod migrations;
+pub mod layers;
+pub mod gridselector;
+pub mod resolution;
+pub mod coords;
+pub mod cursor;
+pub mod editor;
+pub mod palette;
+pub mod collision_overlay;
+pub mod history;
+pub mod tools;
+pub mod tileset;
+pub mod tileset_loader;
+pub mod tiled;
+pub mod ldtk;
@@ -1,18 +1,11 @@
 #![windows_subsystem = "windows"]
-pub mod player;
-mod game;
-mod map;
-mod layers;
-mod gridselector;
-mod resolution;
-mod cursor;
-
 use bevy::prelude::*;
 use bevy::window::{CursorOptions, WindowMode};
 use bevy::{
     dev_tools::fps_overlay::{FpsOverlayConfig, FpsOverlayPlugin},
 };
 use bevy::text::FontSmoothing;
+use rpggame::game;
 
 struct OverlayColor;
 
@@ -1,51 +1,243 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
+use bevy::asset::AssetLoadFailedEvent;
 use bevy::prelude::*;
 use bevy::reflect::erased_serde::__private::serde::{Deserialize, Serialize};
 use bevy::render::view::RenderLayers;
+use crate::autotile::AutotileRule;
+use crate::{binary_map, migrations};
+use crate::game::MainCamera;
+use crate::map_loader::MapLoader;
+use crate::player::PLAYER_Z;
 use crate::layers::{MAP_LAYER};
-use rand::Rng;
-use crate::resolution;
+use crate::{coords, resolution};
+use crate::tileset::{valid_movement_cost, TileOverrides, TileProperties, TilesetManifest, TilesetRegistry};
 
 pub struct MapPlugin;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-enum Collision {
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Collision {
     Full,
     Middle,
     Slow,
+    #[default]
     Passable,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-enum MapLayer {
+impl Collision {
+    pub const ALL: [Collision; 4] = [Collision::Full, Collision::Middle, Collision::Slow, Collision::Passable];
+
+    pub fn from_name(name: &str) -> Option<Collision> {
+        Collision::ALL.into_iter().find(|collision| format!("{:?}", collision).eq_ignore_ascii_case(name))
+    }
+
+    pub fn blocks_walking(&self) -> bool {
+        matches!(self, Collision::Full | Collision::Middle)
+    }
+
+    pub fn blocks_projectiles(&self) -> bool {
+        matches!(self, Collision::Full)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        match self {
+            Collision::Slow => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    fn restrictiveness(&self) -> u8 {
+        match self {
+            Collision::Passable => 0,
+            Collision::Slow => 1,
+            Collision::Middle => 2,
+            Collision::Full => 3,
+        }
+    }
+
+    pub fn most_restrictive(self, other: Collision) -> Collision {
+        if other.restrictiveness() > self.restrictiveness() { other } else { self }
+    }
+
+    // Translucent RGBA tint used by the editor overlay, the PNG renderer and editor/collision.png.
+    pub fn tint(&self) -> [u8; 4] {
+        match self {
+            Collision::Full => [220, 40, 40, 140],
+            Collision::Middle => [240, 150, 30, 140],
+            Collision::Slow => [40, 110, 230, 140],
+            Collision::Passable => [60, 200, 90, 60],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MapLayer {
     Base,
     Objects,
     Details,
     Particles,
     Weather
 }
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Block {
-    tile: usize,
-    collision: Collision,
-    texture: String,
+
+impl MapLayer {
+    pub const ALL: [MapLayer; 5] = [MapLayer::Base, MapLayer::Objects, MapLayer::Details, MapLayer::Particles, MapLayer::Weather];
+
+    pub fn from_name(name: &str) -> Option<MapLayer> {
+        MapLayer::ALL.into_iter().find(|layer| format!("{:?}", layer).eq_ignore_ascii_case(name))
+    }
+
+    // Particles and weather are drawn over the player, the other layers under it.
+    pub fn z(&self) -> f32 {
+        match self {
+            MapLayer::Base => 0.0,
+            MapLayer::Objects => 1.0,
+            MapLayer::Details => 2.0,
+            MapLayer::Particles => PLAYER_Z + 1.0,
+            MapLayer::Weather => PLAYER_Z + 2.0,
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Chunk {
-    blocks: Vec<Vec<Block>>,
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Block {
+    pub tile: usize,
+    pub texture: String,
+    #[serde(flatten)]
+    pub overrides: TileOverrides,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Map {
-    chunks: Vec<Vec<Chunk>>,
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub blocks: Vec<Vec<Block>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub layers: BTreeMap<MapLayer, Vec<Vec<Option<Block>>>>,
+}
+
+impl Chunk {
+    pub fn layer_blocks(&self, layer: MapLayer) -> Vec<(usize, usize, &Block)> {
+        if layer == MapLayer::Base {
+            return self.blocks.iter().enumerate()
+                .flat_map(|(i, row)| row.iter().enumerate().map(move |(j, block)| (i, j, block)))
+                .collect();
+        }
+
+        self.layers.get(&layer).into_iter()
+            .flat_map(|grid| grid.iter().enumerate())
+            .flat_map(|(i, row)| row.iter().enumerate().filter_map(move |(j, block)| block.as_ref().map(|block| (i, j, block))))
+            .collect()
+    }
+
+    fn row_lengths(&self) -> Vec<(MapLayer, Vec<usize>)> {
+        let mut lengths = vec![(MapLayer::Base, self.blocks.iter().map(Vec::len).collect())];
+        for (layer, grid) in &self.layers {
+            lengths.push((*layer, grid.iter().map(Vec::len).collect()));
+        }
+        lengths
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockLocation {
+    pub chunk: UVec2,
+    pub layer: MapLayer,
+    pub block: UVec2,
+}
+
+impl fmt::Display for BlockLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chunk ({}, {}) block ({}, {}) on the {:?} layer", self.chunk.x, self.chunk.y, self.block.x, self.block.y, self.layer)
+    }
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Binary(String),
+    UnsupportedVersion { found: u64, supported: u64 },
+    Render(String),
+    Import(String),
+    UnknownTexture { texture: String, location: BlockLocation },
+    TileOutOfRange { tile: usize, tile_count: usize, location: BlockLocation },
+    InvalidMovementCost { cost: f32, texture: String, tile: usize, location: Option<BlockLocation> },
+    RaggedChunk { chunk: UVec2, layer: MapLayer, row: Option<usize>, expected: usize },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Io(error) => write!(f, "could not read map file: {}", error),
+            MapError::Parse(error) => write!(f, "could not parse map file: {}", error),
+            MapError::Binary(message) => write!(f, "invalid binary map: {}", message),
+            MapError::UnsupportedVersion { found, supported } => {
+                write!(f, "map version {} is newer than the supported version {}", found, supported)
+            }
+            MapError::Render(message) => write!(f, "could not render map: {}", message),
+            MapError::Import(message) => write!(f, "could not import map: {}", message),
+            MapError::UnknownTexture { texture, location } => write!(f, "unknown texture \"{}\" at {}", texture, location),
+            MapError::TileOutOfRange { tile, tile_count, location } => {
+                write!(f, "tile {} at {} is out of range, the texture has {} tiles", tile, location, tile_count)
+            }
+            MapError::InvalidMovementCost { cost, texture, tile, location: Some(location) } => {
+                write!(f, "movement cost {} of tile {} of \"{}\" at {} must be a positive number", cost, tile, texture, location)
+            }
+            MapError::InvalidMovementCost { cost, texture, tile, location: None } => {
+                write!(f, "movement cost {} of tile {} in tileset \"{}\" must be a positive number", cost, tile, texture)
+            }
+            MapError::RaggedChunk { chunk, layer, row: Some(row), expected } => {
+                write!(f, "row {} of chunk ({}, {}) on the {:?} layer does not have {} blocks", row, chunk.x, chunk.y, layer, expected)
+            }
+            MapError::RaggedChunk { chunk, layer, row: None, expected } => {
+                write!(f, "chunk ({}, {}) on the {:?} layer does not have {} rows", chunk.x, chunk.y, layer, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MapError::Io(error) => Some(error),
+            MapError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MapError {
+    fn from(error: std::io::Error) -> Self {
+        MapError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for MapError {
+    fn from(error: serde_json::Error) -> Self {
+        MapError::Parse(error)
+    }
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Map {
+    pub chunk_size: usize,
+    pub chunks: Vec<Vec<Chunk>>,
+}
+
+#[derive(Serialize)]
+struct VersionedMap<'a> {
+    version: u64,
+    #[serde(flatten)]
+    map: &'a Map,
 }
 
 impl Map {
-    pub fn save_to_json(&self, path: &str) -> Result<(), std::io::Error> {
-        let json = serde_json::to_string_pretty(self)?;
+    pub fn to_json_string(&self) -> Result<String, MapError> {
+        Ok(serde_json::to_string_pretty(&VersionedMap { version: migrations::CURRENT_VERSION, map: self })?)
+    }
+
+    pub fn save_to_json(&self, path: &str) -> Result<(), MapError> {
+        let json = self.to_json_string()?;
 
         let mut file = OpenOptions::new()
             .write(true)
@@ -58,105 +250,526 @@ impl Map {
         Ok(())
     }
 
-    pub fn load_from_json(path: &str) -> Result<Self, std::io::Error> {
+    pub fn to_binary(&self, compress: bool) -> Result<Vec<u8>, MapError> {
+        binary_map::encode(self, compress)
+    }
+
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, MapError> {
+        let map = binary_map::decode(bytes)?;
+        map.validate_chunks()?;
+
+        Ok(map)
+    }
+
+    pub fn save_to_binary(&self, path: &str, compress: bool) -> Result<(), MapError> {
+        let bytes = self.to_binary(compress)?;
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        file.write_all(&bytes)?;
+
+        Ok(())
+    }
+
+    pub fn load_from_binary(path: &str) -> Result<Self, MapError> {
+        let mut file = File::open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        Self::from_binary(&bytes)
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, MapError> {
+        let mut file = File::open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        if binary_map::is_binary(&bytes) {
+            return Self::from_binary(&bytes);
+        }
+        Self::from_json_slice(&bytes)
+    }
+
+    pub fn save_to_file(&self, path: &str, compress: bool) -> Result<(), MapError> {
+        if path.ends_with(".map.bin") {
+            return self.save_to_binary(path, compress);
+        }
+        self.save_to_json(path)
+    }
+
+    pub fn load_from_json(path: &str) -> Result<Self, MapError> {
         let mut file = File::open(path)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
 
-        let map: Map = serde_json::from_str(&contents)?;
+        Self::from_json_str(&contents)
+    }
+
+    pub fn from_json_str(contents: &str) -> Result<Self, MapError> {
+        Self::from_json_slice(contents.as_bytes())
+    }
+
+    pub fn from_json_slice(contents: &[u8]) -> Result<Self, MapError> {
+        let value = migrations::migrate(serde_json::from_slice(contents)?)?;
+        let map: Map = serde_json::from_value(value)?;
+        map.validate_chunks()?;
 
         Ok(map)
     }
-}
 
-fn generate_map(size: usize) -> Vec<Vec<usize>> {
-    let mut map_data = vec![vec![0; size]; size];
+    // Builds a width x height block map, filling Base cells the grids leave empty.
+    pub fn from_layer_grids(chunk_size: usize, width: usize, height: usize, grids: &BTreeMap<MapLayer, Vec<Vec<Option<Block>>>>, fill: &Block) -> Self {
+        let cell = |layer: &MapLayer, x: usize, y: usize| {
+            grids.get(layer).and_then(|grid| grid.get(y)).and_then(|row| row.get(x)).cloned().flatten()
+        };
+
+        let chunks = (0..height.div_ceil(chunk_size)).map(|chunk_y| {
+            (0..width.div_ceil(chunk_size)).map(|chunk_x| {
+                let rows = chunk_y * chunk_size..(chunk_y + 1) * chunk_size;
+                let columns = chunk_x * chunk_size..(chunk_x + 1) * chunk_size;
+                let blocks = rows.clone()
+                    .map(|y| columns.clone().map(|x| cell(&MapLayer::Base, x, y).unwrap_or_else(|| fill.clone())).collect())
+                    .collect();
+                let layers = grids.keys()
+                    .filter(|layer| **layer != MapLayer::Base)
+                    .map(|layer| (*layer, rows.clone().map(|y| columns.clone().map(|x| cell(layer, x, y)).collect()).collect::<Vec<Vec<_>>>()))
+                    .filter(|(_, grid)| grid.iter().flatten().any(Option::is_some))
+                    .collect();
+                Chunk { blocks, layers }
+            }).collect()
+        }).collect();
+
+        Map { chunk_size, chunks }
+    }
+
+    pub fn block_at(&self, x: isize, y: isize) -> Option<&Block> {
+        if x < 0 || y < 0 || self.chunk_size == 0 {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let chunk = self.chunks.get(y / self.chunk_size)?.get(x / self.chunk_size)?;
+
+        chunk.blocks.get(y % self.chunk_size)?.get(x % self.chunk_size)
+    }
+
+    pub fn block_at_mut(&mut self, x: isize, y: isize) -> Option<&mut Block> {
+        if x < 0 || y < 0 || self.chunk_size == 0 {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let chunk = self.chunks.get_mut(y / self.chunk_size)?.get_mut(x / self.chunk_size)?;
+
+        chunk.blocks.get_mut(y % self.chunk_size)?.get_mut(x % self.chunk_size)
+    }
+
+    pub fn layer_block_at(&self, x: isize, y: isize, layer: MapLayer) -> Option<&Block> {
+        if layer == MapLayer::Base {
+            return self.block_at(x, y);
+        }
+        if x < 0 || y < 0 || self.chunk_size == 0 {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let chunk = self.chunks.get(y / self.chunk_size)?.get(x / self.chunk_size)?;
+
+        chunk.layers.get(&layer)?.get(y % self.chunk_size)?.get(x % self.chunk_size)?.as_ref()
+    }
+
+    // Replaces the block of one layer and returns what was there before, or None when nothing changed
+    // because the block is outside the map or Base would be left empty.
+    pub fn set_block(&mut self, x: isize, y: isize, layer: MapLayer, block: Option<Block>) -> Option<Option<Block>> {
+        if layer == MapLayer::Base {
+            let target = self.block_at_mut(x, y)?;
+            return Some(Some(std::mem::replace(target, block?)));
+        }
+        if x < 0 || y < 0 || self.chunk_size == 0 {
+            return None;
+        }
+        let chunk_size = self.chunk_size;
+        let (x, y) = (x as usize, y as usize);
+        let chunk = self.chunks.get_mut(y / chunk_size)?.get_mut(x / chunk_size)?;
+        if block.is_none() && !chunk.layers.contains_key(&layer) {
+            return Some(None);
+        }
+
+        let grid = chunk.layers.entry(layer).or_insert_with(|| vec![vec![None; chunk_size]; chunk_size]);
+        let previous = std::mem::replace(grid.get_mut(y % chunk_size)?.get_mut(x % chunk_size)?, block);
+        // Empty layers are dropped so an edited map serializes like one that never had the layer.
+        if grid.iter().flatten().all(Option::is_none) {
+            chunk.layers.remove(&layer);
+        }
+        Some(previous)
+    }
+
+    pub fn chunk_of(&self, x: isize, y: isize) -> Option<IVec2> {
+        if x < 0 || y < 0 || self.chunk_size == 0 {
+            return None;
+        }
+        Some(coords::block_to_chunk(IVec2::new(x as i32, y as i32), self.chunk_size).0)
+    }
 
-    for i in 0..size {
-        map_data[0][i] = 1;
-        map_data[size - 1][i] = 23;
-        map_data[i][0] = 11;
-        map_data[i][size - 1] = 13;
+    fn is_terrain(&self, x: isize, y: isize, texture: &str, rule: &AutotileRule) -> bool {
+        self.block_at(x, y).is_some_and(|block| block.texture == texture && rule.contains_tile(block.tile))
     }
-    map_data[0][0] = 41;
-    map_data[0][size - 1] = 43;
-    map_data[size - 1][0] = 22;
-    map_data[size - 1][size - 1] = 24;
 
-    let mut rng = rand::thread_rng();
-    for i in 1..size - 1 {
-        for j in 1..size - 1 {
-            map_data[i][j] = 12;
-            if rng.gen_bool(0.1) { // 30% chance to randomize
-                map_data[i][j] = rng.gen_range(56..=60);
+    // Recomputes the edge and corner tiles of the terrain around a block after it was painted. Fully
+    // surrounded blocks keep their decoration variant.
+    pub fn autotile_around(&mut self, x: isize, y: isize, texture: &str, rule: &AutotileRule) {
+        for neighbour_y in y - 1..=y + 1 {
+            for neighbour_x in x - 1..=x + 1 {
+                if !self.is_terrain(neighbour_x, neighbour_y, texture, rule) {
+                    continue;
+                }
+                let mask = rule.neighbour_mask(neighbour_x, neighbour_y, |bx, by| self.is_terrain(bx, by, texture, rule));
+                let tile = rule.tile_for_mask(mask);
+                if let Some(block) = self.block_at_mut(neighbour_x, neighbour_y) {
+                    if mask != rule.full_mask() || !rule.variants.contains(&block.tile) {
+                        block.tile = tile;
+                    }
+                }
             }
         }
     }
-    map_data
+
+    pub fn block_at_world(&self, position: Vec2, resolution: &resolution::Resolution) -> Option<&Block> {
+        let block = coords::world_to_block(position, resolution);
+
+        self.block_at(block.x as isize, block.y as isize)
+    }
+
+    fn overlay_blocks_at(&self, x: isize, y: isize) -> Vec<&Block> {
+        if x < 0 || y < 0 || self.chunk_size == 0 {
+            return Vec::new();
+        }
+        let (x, y) = (x as usize, y as usize);
+        let Some(chunk) = self.chunks.get(y / self.chunk_size).and_then(|row| row.get(x / self.chunk_size)) else {
+            return Vec::new();
+        };
+
+        chunk.layers.values()
+            .filter_map(|grid| grid.get(y % self.chunk_size)?.get(x % self.chunk_size)?.as_ref())
+            .collect()
+    }
+
+    // Tileset defaults with the block's overrides applied. Blocks on the overlay layers can only make the
+    // collision more restrictive, so a rock on the Objects layer blocks the grass underneath it.
+    pub fn properties_at(&self, x: isize, y: isize, tilesets: &TilesetManifest) -> Option<TileProperties> {
+        let block = self.block_at(x, y)?;
+        let mut properties = tilesets.tile_properties(&block.texture, block.tile).with_overrides(&block.overrides);
+
+        for overlay in self.overlay_blocks_at(x, y) {
+            let overlay_properties = tilesets.tile_properties(&overlay.texture, overlay.tile).with_overrides(&overlay.overrides);
+            properties.collision = properties.collision.most_restrictive(overlay_properties.collision);
+        }
+
+        Some(properties)
+    }
+
+    pub fn properties_at_world(&self, position: Vec2, tilesets: &TilesetManifest, resolution: &resolution::Resolution) -> Option<TileProperties> {
+        let block = coords::world_to_block(position, resolution);
+
+        self.properties_at(block.x as isize, block.y as isize, tilesets)
+    }
+
+    fn validate_chunks(&self) -> Result<(), MapError> {
+        for (chunk_y, row) in self.chunks.iter().enumerate() {
+            for (chunk_x, chunk) in row.iter().enumerate() {
+                for (layer, lengths) in chunk.row_lengths() {
+                    let ragged = |row: Option<usize>| MapError::RaggedChunk {
+                        chunk: UVec2::new(chunk_x as u32, chunk_y as u32),
+                        layer,
+                        row,
+                        expected: self.chunk_size,
+                    };
+                    if lengths.len() != self.chunk_size {
+                        return Err(ragged(None));
+                    }
+                    if let Some(row) = lengths.iter().position(|length| *length != self.chunk_size) {
+                        return Err(ragged(Some(row)));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_tiles(&self, tile_counts: &HashMap<String, usize>) -> Result<(), MapError> {
+        for (chunk_y, row) in self.chunks.iter().enumerate() {
+            for (chunk_x, chunk) in row.iter().enumerate() {
+                for layer in MapLayer::ALL {
+                    for (i, j, block) in chunk.layer_blocks(layer) {
+                        let location = BlockLocation {
+                            chunk: UVec2::new(chunk_x as u32, chunk_y as u32),
+                            layer,
+                            block: UVec2::new(j as u32, i as u32),
+                        };
+                        let Some(&tile_count) = tile_counts.get(&block.texture) else {
+                            return Err(MapError::UnknownTexture { texture: block.texture.clone(), location });
+                        };
+                        if block.tile >= tile_count {
+                            return Err(MapError::TileOutOfRange { tile: block.tile, tile_count, location });
+                        }
+                        if let Some(cost) = block.overrides.movement_cost.filter(|cost| !valid_movement_cost(*cost)) {
+                            return Err(MapError::InvalidMovementCost { cost, texture: block.texture.clone(), tile: block.tile, location: Some(location) });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_map);
+        app
+            .init_asset::<Map>()
+            .init_asset_loader::<MapLoader>()
+            .insert_resource(ChunkStreaming::default())
+            .insert_resource(LoadedChunks::default())
+            .add_event::<ChunkChanged>()
+            .add_event::<MapReplaced>()
+            .add_systems(Startup, setup_map)
+            .add_systems(Update, (report_map_load_failures, apply_loaded_map.run_if(resource_exists::<TilesetRegistry>), refresh_changed_chunks, stream_chunks.run_if(resource_exists::<LoadedMap>)).chain());
     }
 }
 
+pub const MAP_PATH: &str = "maps/main.map.json";
+
+#[derive(Resource)]
+pub struct ChunkStreaming {
+    pub radius: i32,
+}
+
+impl Default for ChunkStreaming {
+    fn default() -> Self {
+        Self { radius: 2 }
+    }
+}
+
+#[derive(Resource)]
+pub struct MapHandle(pub Handle<Map>);
+
+#[derive(Resource)]
+pub struct LoadedMap {
+    pub map: Map,
+}
+
+#[derive(Resource)]
+pub struct MapLoadError(pub String);
+
+#[derive(Component)]
+pub struct MapErrorText;
+
+// Streamed chunk entities by chunk position.
+#[derive(Resource, Default)]
+pub struct LoadedChunks(pub HashMap<IVec2, Entity>);
+
+#[derive(Component)]
+pub struct MapChunk(pub IVec2);
+
+// Sent after LoadedMap was edited in place so the chunk gets respawned from the new data.
+#[derive(Event)]
+pub struct ChunkChanged(pub IVec2);
+
+// Sent when LoadedMap was replaced by a different map from disk, which invalidates edits made to the old one.
+#[derive(Event)]
+pub struct MapReplaced;
+
 fn setup_map(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    resolution: Res<resolution::Resolution>,
 ) {
-    let texture: Handle<Image> = asset_server.load("nature/ground/nature_ground.png");
-    let frame_size = resolution.frame_size;
-    let spacing = UVec2::new(0, 0);
-    let offset = UVec2::new(0, 0);
-    let layout = TextureAtlasLayout::from_grid(frame_size, 11, 7, Option::from(spacing), Option::from(offset));
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
+    // let map = generator::generate(&params, &manifest).unwrap();
+    //
+    // map.save_to_json("assets/maps/main.map.json").unwrap();
 
-    let invert = 1.0;
-    let textures: HashMap<String, Handle<Image>> = [
-        ("grass".to_string(), texture.clone()),
-    ].iter().cloned().collect();
+    commands.insert_resource(MapHandle(asset_server.load(MAP_PATH)));
+}
 
-    // let map_data = generate_map(16);
-    //
-    // let mut blocks = Vec::new();
-    // for row in map_data.iter() {
-    //     let mut block_row = Vec::new();
-    //     for &tile in row.iter() {
-    //         block_row.push(Block {
-    //             tile,
-    //             texture: String::from("grass"),
-    //             collision: Collision::Passable,
-    //         });
-    //     }
-    //     blocks.push(block_row);
-    // }
-    //
-    // let chunk = Chunk { blocks };
-    // let map = Map { chunks: vec![vec![chunk.clone()]] };
-    //
-    // map.save_to_json("maps/main.json").unwrap();
+fn report_map_load_failures(
+    mut commands: Commands,
+    mut failed_events: EventReader<AssetLoadFailedEvent<Map>>,
+    map_handle: Res<MapHandle>,
+    q_error_text: Query<Entity, With<MapErrorText>>,
+) {
+    for event in failed_events.read() {
+        if event.id == map_handle.0.id() {
+            show_map_error(&mut commands, &q_error_text, event.error.to_string());
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_loaded_map(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<Map>>,
+    maps: Res<Assets<Map>>,
+    map_handle: Res<MapHandle>,
+    tilesets: Res<TilesetRegistry>,
+    loaded_map: Option<Res<LoadedMap>>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    mut replaced_events: EventWriter<MapReplaced>,
+    q_error_text: Query<Entity, With<MapErrorText>>,
+) {
+    let changed = asset_events.read().any(|event| match event {
+        AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id == map_handle.0.id(),
+        _ => false,
+    });
+    // A reloaded tileset manifest can make the map valid or invalid, so it is checked again.
+    if !changed && !tilesets.is_changed() {
+        return;
+    }
+    // Only a changed map file replaces the map in memory; on a tileset change unsaved edits stay.
+    let kept_map = loaded_map.as_ref().filter(|_| !changed).map(|loaded_map| &loaded_map.map);
+    let Some(map) = kept_map.or_else(|| maps.get(&map_handle.0)) else {
+        return;
+    };
+
+    if let Err(error) = map.validate_tiles(&tilesets.tile_counts()) {
+        show_map_error(&mut commands, &q_error_text, error.to_string());
+        return;
+    }
+
+    for entity in &q_error_text {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<MapLoadError>();
+
+    // Respawn every streamed chunk so edits to the map or tileset files show up immediately.
+    for (_, entity) in loaded_chunks.0.drain() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if kept_map.is_some() {
+        return;
+    }
+    // Saving from the editor reloads the same map, which keeps the edit history usable.
+    if loaded_map.as_ref().is_none_or(|loaded_map| loaded_map.map != *map) {
+        replaced_events.send(MapReplaced);
+    }
+    commands.insert_resource(LoadedMap { map: map.clone() });
+}
+
+fn refresh_changed_chunks(
+    mut commands: Commands,
+    mut changed_events: EventReader<ChunkChanged>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+) {
+    for ChunkChanged(coords) in changed_events.read() {
+        if let Some(entity) = loaded_chunks.0.remove(coords) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn show_map_error(commands: &mut Commands, q_error_text: &Query<Entity, With<MapErrorText>>, error: String) {
+    error!("Failed to load {}: {}", MAP_PATH, error);
 
-    let map = Map::load_from_json("maps/main.json").unwrap();
+    for entity in q_error_text {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn((
+        Text::new(format!("Failed to load map: {}", error)),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(80.0),
+            left: Val::Px(0.0),
+            ..default()
+        },
+        MapErrorText,
+    ));
+    commands.insert_resource(MapLoadError(error));
+}
 
-    let chunk = &map.chunks[0][0];
+pub fn stream_chunks(
+    mut commands: Commands,
+    streaming: Res<ChunkStreaming>,
+    loaded_map: Res<LoadedMap>,
+    tilesets: Res<TilesetRegistry>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    q_camera: Query<&GlobalTransform, With<MainCamera>>,
+    resolution: Res<resolution::Resolution>,
+) {
+    let Ok(camera_transform) = q_camera.get_single() else {
+        return;
+    };
 
-    for (i, row) in chunk.blocks.iter().enumerate() {
-        for (j, block) in row.iter().enumerate() {
-            commands.spawn((
-                Sprite::from_atlas_image(
-                    textures.get(&block.texture).unwrap().clone(),
-                    TextureAtlas {
-                        layout: texture_atlas_layout.clone(),
-                        index: block.tile,
-                    },
-                ),
-                Transform::from_scale(Vec3::new(resolution.pixel_ratio as f32 * invert, resolution.pixel_ratio as f32, 1.0))
-                    .with_translation(Vec3::new(j as f32 * resolution.map_translation.x, i as f32 * resolution.map_translation.y, 0.0)),
-                RenderLayers::layer(MAP_LAYER),
-            ));
+    let map = &loaded_map.map;
+    if map.chunk_size == 0 {
+        return;
+    }
+    let center = coords::world_to_chunk(camera_transform.translation().truncate(), map.chunk_size, &resolution);
+    let in_range = |coords: IVec2| (coords - center).abs().max_element() <= streaming.radius;
+
+    loaded_chunks.0.retain(|coords, entity| {
+        if in_range(*coords) {
+            return true;
+        }
+        commands.entity(*entity).despawn_recursive();
+        false
+    });
+
+    for chunk_y in (center.y - streaming.radius)..=(center.y + streaming.radius) {
+        for chunk_x in (center.x - streaming.radius)..=(center.x + streaming.radius) {
+            let coords = IVec2::new(chunk_x, chunk_y);
+            if loaded_chunks.0.contains_key(&coords) || chunk_x < 0 || chunk_y < 0 {
+                continue;
+            }
+            let Some(chunk) = map.chunks.get(chunk_y as usize).and_then(|row| row.get(chunk_x as usize)) else {
+                continue;
+            };
+
+            let entity = spawn_chunk(&mut commands, chunk, coords, map.chunk_size, &tilesets, &resolution);
+            loaded_chunks.0.insert(coords, entity);
         }
     }
-}
\ No newline at end of file
+}
+
+fn spawn_chunk(
+    commands: &mut Commands,
+    chunk: &Chunk,
+    chunk_coords: IVec2,
+    chunk_size: usize,
+    tilesets: &TilesetRegistry,
+    resolution: &resolution::Resolution,
+) -> Entity {
+    let invert = 1.0;
+    let chunk_offset = coords::chunk_to_world(chunk_coords, chunk_size, resolution);
+
+    commands.spawn((
+        Transform::from_translation(chunk_offset.extend(0.0)),
+        Visibility::default(),
+        MapChunk(chunk_coords),
+    )).with_children(|parent| {
+        for layer in MapLayer::ALL {
+            for (i, j, block) in chunk.layer_blocks(layer) {
+                let Some(tileset) = tilesets.get(&block.texture) else {
+                    continue;
+                };
+                parent.spawn((
+                    Sprite::from_atlas_image(
+                        tileset.image.clone(),
+                        TextureAtlas {
+                            layout: tileset.layout.clone(),
+                            index: block.tile,
+                        },
+                    ),
+                    Transform::from_scale(Vec3::new(resolution.pixel_ratio as f32 * invert, resolution.pixel_ratio as f32, 1.0))
+                        .with_translation(coords::block_to_world(IVec2::new(j as i32, i as i32), resolution).extend(layer.z())),
+                    RenderLayers::layer(MAP_LAYER),
+                ));
+            }
+        }
+    }).id()
+}
@@ -0,0 +1,32 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use crate::binary_map;
+use crate::map::{Map, MapError};
+
+#[derive(Default)]
+pub struct MapLoader;
+
+impl AssetLoader for MapLoader {
+    type Asset = Map;
+    type Settings = ();
+    type Error = MapError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        if binary_map::is_binary(&bytes) {
+            return Map::from_binary(&bytes);
+        }
+        Map::from_json_slice(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["map.json", "map.bin"]
+    }
+}
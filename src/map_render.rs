@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use image::{imageops, GenericImageView, Rgba, RgbaImage};
+use crate::map::{Collision, Map, MapError, MapLayer};
+use crate::tileset::{TilesetDefinition, TilesetManifest};
+
+pub struct RenderOptions {
+    pub layers: Vec<MapLayer>,
+    pub collision_overlay: bool,
+    pub cell_size: u32,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            layers: MapLayer::ALL.to_vec(),
+            collision_overlay: false,
+            cell_size: 16,
+        }
+    }
+}
+
+pub fn load_tileset_images(tilesets: &TilesetManifest, assets_root: &str) -> Result<HashMap<String, RgbaImage>, MapError> {
+    tilesets.tilesets.iter().map(|tileset| {
+        let path = format!("{}/{}", assets_root, tileset.image);
+        let image = image::open(&path).map_err(|error| MapError::Render(format!("could not open {}: {}", path, error)))?;
+        Ok((tileset.name.clone(), image.to_rgba8()))
+    }).collect()
+}
+
+fn collision_tint(collision: Collision) -> Option<Rgba<u8>> {
+    // Passable blocks stay untinted so the preview still shows the map.
+    (collision != Collision::Passable).then(|| Rgba(collision.tint()))
+}
+
+fn tile_image(image: &RgbaImage, tileset: &TilesetDefinition, tile: usize) -> Option<RgbaImage> {
+    if tile >= tileset.tile_count() {
+        return None;
+    }
+    let [width, height] = tileset.tile_size;
+    let column = tile as u32 % tileset.columns;
+    let row = tile as u32 / tileset.columns;
+    // Same rectangles as TextureAtlasLayout::from_grid: offset, then tile size plus spacing per cell.
+    let x = tileset.offset[0] + column * (width + tileset.spacing[0]);
+    let y = tileset.offset[1] + row * (height + tileset.spacing[1]);
+    if x + width > image.width() || y + height > image.height() {
+        return None;
+    }
+    Some(image.view(x, y, width, height).to_image())
+}
+
+pub fn render_map(
+    map: &Map,
+    tilesets: &TilesetManifest,
+    images: &HashMap<String, RgbaImage>,
+    options: &RenderOptions,
+) -> Result<RgbaImage, MapError> {
+    let chunk_columns = map.chunks.iter().map(Vec::len).max().unwrap_or(0);
+    let width = (chunk_columns * map.chunk_size) as u32 * options.cell_size;
+    let height = (map.chunks.len() * map.chunk_size) as u32 * options.cell_size;
+    let mut canvas = RgbaImage::new(width, height);
+
+    for layer in MapLayer::ALL.iter().filter(|layer| options.layers.contains(layer)) {
+        for (chunk_y, row) in map.chunks.iter().enumerate() {
+            for (chunk_x, chunk) in row.iter().enumerate() {
+                for (i, j, block) in chunk.layer_blocks(*layer) {
+                    let (Some(tileset), Some(image)) = (tilesets.get(&block.texture), images.get(&block.texture)) else {
+                        return Err(MapError::Render(format!("no image for texture \"{}\"", block.texture)));
+                    };
+                    let Some(tile) = tile_image(image, tileset, block.tile) else {
+                        return Err(MapError::Render(format!("tile {} is outside the \"{}\" image", block.tile, block.texture)));
+                    };
+
+                    let cell_x = (chunk_x * map.chunk_size + j) as i64 * options.cell_size as i64;
+                    let cell_y = (chunk_y * map.chunk_size + i) as i64 * options.cell_size as i64;
+                    let x = cell_x + (options.cell_size as i64 - tile.width() as i64) / 2;
+                    let y = cell_y + (options.cell_size as i64 - tile.height() as i64) / 2;
+                    imageops::overlay(&mut canvas, &tile, x, y);
+                }
+            }
+        }
+    }
+
+    if options.collision_overlay {
+        let blocks_wide = chunk_columns * map.chunk_size;
+        let blocks_high = map.chunks.len() * map.chunk_size;
+        for y in 0..blocks_high {
+            for x in 0..blocks_wide {
+                let Some(properties) = map.properties_at(x as isize, y as isize, tilesets) else {
+                    continue;
+                };
+                if let Some(tint) = collision_tint(properties.collision) {
+                    let cell = RgbaImage::from_pixel(options.cell_size, options.cell_size, tint);
+                    imageops::overlay(&mut canvas, &cell, (x as u32 * options.cell_size) as i64, (y as u32 * options.cell_size) as i64);
+                }
+            }
+        }
+    }
+
+    Ok(canvas)
+}
+
+// One image per selected layer, each covering the whole map so they can be stacked again in an editor.
+pub fn render_map_layers(
+    map: &Map,
+    tilesets: &TilesetManifest,
+    images: &HashMap<String, RgbaImage>,
+    options: &RenderOptions,
+) -> Result<Vec<(MapLayer, RgbaImage)>, MapError> {
+    MapLayer::ALL.into_iter().filter(|layer| options.layers.contains(layer)).map(|layer| {
+        let layer_options = RenderOptions { layers: vec![layer], collision_overlay: options.collision_overlay, cell_size: options.cell_size };
+        Ok((layer, render_map(map, tilesets, images, &layer_options)?))
+    }).collect()
+}
+
+// preview.png becomes preview.objects.png for the Objects layer.
+pub fn layer_image_path(output: &str, layer: MapLayer) -> String {
+    let name = format!("{:?}", layer).to_lowercase();
+    match output.rsplit_once('.').filter(|(_, extension)| !extension.contains(['/', '\\'])) {
+        Some((stem, extension)) => format!("{}.{}.{}", stem, name, extension),
+        None => format!("{}.{}", output, name),
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::map::{Collision, Map, MapLayer};
+use crate::tileset::TilesetManifest;
+
+pub struct MapStats {
+    pub chunk_count: usize,
+    pub chunk_size: usize,
+    pub block_count: usize,
+    pub overlay_block_count: usize,
+    pub tile_histogram: BTreeMap<(String, usize), usize>,
+    pub collision_counts: BTreeMap<String, usize>,
+}
+
+impl MapStats {
+    pub fn collect(map: &Map, tilesets: &TilesetManifest) -> Self {
+        let mut stats = MapStats {
+            chunk_count: 0,
+            chunk_size: map.chunk_size,
+            block_count: 0,
+            overlay_block_count: 0,
+            tile_histogram: BTreeMap::new(),
+            collision_counts: BTreeMap::new(),
+        };
+
+        for (chunk_y, row) in map.chunks.iter().enumerate() {
+            for (chunk_x, chunk) in row.iter().enumerate() {
+                stats.chunk_count += 1;
+                for layer in MapLayer::ALL {
+                    for (i, j, block) in chunk.layer_blocks(layer) {
+                        *stats.tile_histogram.entry((block.texture.clone(), block.tile)).or_default() += 1;
+                        if layer != MapLayer::Base {
+                            stats.overlay_block_count += 1;
+                            continue;
+                        }
+
+                        stats.block_count += 1;
+                        let x = (chunk_x * map.chunk_size + j) as isize;
+                        let y = (chunk_y * map.chunk_size + i) as isize;
+                        let collision = map.properties_at(x, y, tilesets).map_or(Collision::Passable, |properties| properties.collision);
+                        *stats.collision_counts.entry(format!("{:?}", collision)).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        stats
+    }
+
+    pub fn collision_coverage(&self, collision: Collision) -> f32 {
+        if self.block_count == 0 {
+            return 0.0;
+        }
+        let count = self.collision_counts.get(&format!("{:?}", collision)).copied().unwrap_or(0);
+        count as f32 / self.block_count as f32
+    }
+}
+
+impl fmt::Display for MapStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "chunks: {} ({}x{} blocks each)", self.chunk_count, self.chunk_size, self.chunk_size)?;
+        writeln!(f, "blocks: {} base, {} on overlay layers", self.block_count, self.overlay_block_count)?;
+        writeln!(f, "collision coverage:")?;
+        for collision in [Collision::Passable, Collision::Slow, Collision::Middle, Collision::Full] {
+            writeln!(f, "  {:?}: {:.1}%", collision, self.collision_coverage(collision) * 100.0)?;
+        }
+        writeln!(f, "tile histogram:")?;
+        for ((texture, tile), count) in &self.tile_histogram {
+            writeln!(f, "  {}:{} {}", texture, tile, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use serde_json::Value;
+use crate::map::MapError;
+
+// Version history of the JSON map schema:
+//   0 - a bare `chunks` grid, every block carrying its collision.
+//   1 - `chunk_size` stored next to the chunks.
+//   2 - `version` stored in the file; block collision became an optional override of the tileset.
+pub const CURRENT_VERSION: u64 = 2;
+
+const MIGRATIONS: [fn(Value) -> Result<Value, MapError>; CURRENT_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+];
+
+pub fn detect_version(value: &Value) -> u64 {
+    match value.get("version").and_then(Value::as_u64) {
+        Some(version) => version,
+        None if value.get("chunk_size").is_some() => 1,
+        None => 0,
+    }
+}
+
+pub fn migrate(mut value: Value) -> Result<Value, MapError> {
+    let version = detect_version(&value);
+    if version > CURRENT_VERSION {
+        return Err(MapError::UnsupportedVersion { found: version, supported: CURRENT_VERSION });
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        value = migration(value)?;
+    }
+    Ok(value)
+}
+
+fn migrate_v0_to_v1(mut value: Value) -> Result<Value, MapError> {
+    let chunk_size = value
+        .pointer("/chunks/0/0/blocks")
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len);
+    value["chunk_size"] = Value::from(chunk_size);
+    Ok(value)
+}
+
+fn migrate_v1_to_v2(mut value: Value) -> Result<Value, MapError> {
+    // Explicit collisions keep working as per-block overrides, so only the version is new.
+    value["version"] = Value::from(2);
+    Ok(value)
+}
@@ -0,0 +1,435 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use crate::editor::{EditorState, EditorUi, PrefabLibrary};
+use crate::map::{Block, MapLayer};
+use crate::tileset::{TileOverrides, TilesetRegistry};
+use crate::tools::{EditorTool, Stamp};
+
+pub struct PalettePlugin;
+
+const COLUMNS: f32 = 6.0;
+const THUMBNAIL_SIZE: f32 = 32.0;
+const THUMBNAIL_MARGIN: f32 = 2.0;
+const BUTTON_COLOR: Color = Color::srgb(0.2, 0.2, 0.2);
+const ACTIVE_BUTTON_COLOR: Color = Color::srgb(0.3, 0.45, 0.75);
+const SELECTED_TILE_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+// Which tileset of the manifest the palette shows.
+#[derive(Resource, Default)]
+pub struct TilePalette {
+    pub tileset: usize,
+}
+
+#[derive(Component)]
+struct PaletteRoot;
+
+#[derive(Component)]
+struct PaletteScroll;
+
+#[derive(Component)]
+struct PaletteTiles;
+
+#[derive(Component)]
+struct PaletteTilesetText;
+
+#[derive(Component)]
+struct PaletteSelectionText;
+
+#[derive(Component)]
+struct PaletteTile(usize);
+
+#[derive(Component)]
+struct PalettePrefabs;
+
+#[derive(Component)]
+struct PrefabButton(String);
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum PaletteButton {
+    PreviousTileset,
+    NextTileset,
+    Layer(MapLayer),
+    Tool(EditorTool),
+}
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(TilePalette::default())
+            .add_systems(Startup, setup_palette)
+            .add_systems(Update, (
+                update_palette_visibility,
+                (
+                    handle_palette_buttons,
+                    follow_brush_tileset,
+                    select_palette_tile,
+                    select_prefab,
+                    rebuild_prefab_buttons,
+                    rebuild_palette_tiles,
+                    update_palette_highlights,
+                    update_palette_text,
+                ).chain().run_if(resource_exists::<TilesetRegistry>),
+                scroll_palette,
+            ));
+    }
+}
+
+fn button(parent: &mut ChildBuilder, label: &str, action: PaletteButton) {
+    parent.spawn((
+        Button,
+        Node {
+            padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+            margin: UiRect::all(Val::Px(THUMBNAIL_MARGIN)),
+            ..default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+        action,
+    )).with_child((
+        Text::new(label),
+        TextFont {
+            font_size: 10.0,
+            ..default()
+        },
+    ));
+}
+
+fn setup_palette(mut commands: Commands) {
+    let width = COLUMNS * (THUMBNAIL_SIZE + 2.0 * THUMBNAIL_MARGIN + 4.0) + 16.0;
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            right: Val::Px(0.0),
+            width: Val::Px(width),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.85)),
+        Visibility::Hidden,
+        RelativeCursorPosition::default(),
+        EditorUi,
+        PaletteRoot,
+    )).with_children(|parent| {
+        parent.spawn(Node {
+            align_items: AlignItems::Center,
+            ..default()
+        }).with_children(|row| {
+            button(row, "<", PaletteButton::PreviousTileset);
+            row.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 10.0,
+                    ..default()
+                },
+                PaletteTilesetText,
+            ));
+            button(row, ">", PaletteButton::NextTileset);
+        });
+
+        parent.spawn(Node {
+            flex_wrap: FlexWrap::Wrap,
+            ..default()
+        }).with_children(|row| {
+            for layer in MapLayer::ALL {
+                button(row, &format!("{:?}", layer), PaletteButton::Layer(layer));
+            }
+        });
+
+        parent.spawn(Node {
+            flex_wrap: FlexWrap::Wrap,
+            ..default()
+        }).with_children(|row| {
+            for tool in EditorTool::ALL {
+                button(row, &format!("{:?}", tool), PaletteButton::Tool(tool));
+            }
+        });
+
+        parent.spawn((
+            Node {
+                flex_wrap: FlexWrap::Wrap,
+                ..default()
+            },
+            PalettePrefabs,
+        ));
+
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            Node {
+                margin: UiRect::vertical(Val::Px(4.0)),
+                ..default()
+            },
+            PaletteSelectionText,
+        ));
+
+        parent.spawn((
+            Node {
+                flex_grow: 1.0,
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+            ScrollPosition::default(),
+            RelativeCursorPosition::default(),
+            PaletteScroll,
+        )).with_child((
+            Node {
+                flex_wrap: FlexWrap::Wrap,
+                align_content: AlignContent::FlexStart,
+                width: Val::Percent(100.0),
+                ..default()
+            },
+            PaletteTiles,
+        ));
+    });
+}
+
+fn update_palette_visibility(
+    state: Res<EditorState>,
+    mut q_root: Query<&mut Visibility, With<PaletteRoot>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for mut visibility in q_root.iter_mut() {
+        *visibility = if state.enabled { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+fn handle_palette_buttons(
+    q_buttons: Query<(&Interaction, &PaletteButton), Changed<Interaction>>,
+    tilesets: Res<TilesetRegistry>,
+    mut palette: ResMut<TilePalette>,
+    mut state: ResMut<EditorState>,
+) {
+    let count = tilesets.manifest.tilesets.len().max(1);
+    for (interaction, button) in q_buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            PaletteButton::PreviousTileset => palette.tileset = (palette.tileset + count - 1) % count,
+            PaletteButton::NextTileset => palette.tileset = (palette.tileset + 1) % count,
+            PaletteButton::Layer(layer) => state.layer = *layer,
+            PaletteButton::Tool(tool) => {
+                state.tool = *tool;
+                state.drag = None;
+            }
+        }
+    }
+}
+
+// Shows the tileset of a brush picked somewhere else, e.g. with the eyedropper.
+fn follow_brush_tileset(
+    state: Res<EditorState>,
+    tilesets: Res<TilesetRegistry>,
+    mut palette: ResMut<TilePalette>,
+    mut last_texture: Local<Option<String>>,
+) {
+    let texture = state.brush.as_ref().map(|brush| brush.texture.clone());
+    if texture == *last_texture {
+        return;
+    }
+    if let Some(index) = texture.as_ref().and_then(|texture| tilesets.manifest.tilesets.iter().position(|tileset| &tileset.name == texture)) {
+        if palette.tileset != index {
+            palette.tileset = index;
+        }
+    }
+    *last_texture = texture;
+}
+
+fn select_palette_tile(
+    q_tiles: Query<(&Interaction, &PaletteTile), Changed<Interaction>>,
+    tilesets: Res<TilesetRegistry>,
+    palette: Res<TilePalette>,
+    mut state: ResMut<EditorState>,
+) {
+    let Some(tileset) = tilesets.manifest.tilesets.get(palette.tileset) else {
+        return;
+    };
+    for (interaction, PaletteTile(tile)) in q_tiles.iter() {
+        if *interaction == Interaction::Pressed {
+            state.brush = Some(Block { tile: *tile, texture: tileset.name.clone(), overrides: TileOverrides::default() });
+        }
+    }
+}
+
+fn select_prefab(
+    q_prefabs: Query<(&Interaction, &PrefabButton), Changed<Interaction>>,
+    mut state: ResMut<EditorState>,
+) {
+    for (interaction, PrefabButton(path)) in q_prefabs.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match Stamp::load_from_json(path) {
+            Ok(stamp) => {
+                state.stamp = Some(stamp);
+                state.pasting = true;
+            }
+            Err(error) => error!("Failed to load prefab {}: {}", path, error),
+        }
+    }
+}
+
+fn rebuild_prefab_buttons(
+    mut commands: Commands,
+    prefabs: Option<Res<PrefabLibrary>>,
+    q_container: Query<Entity, With<PalettePrefabs>>,
+) {
+    let Some(prefabs) = prefabs.filter(|prefabs| prefabs.is_changed()) else {
+        return;
+    };
+    let Ok(container) = q_container.get_single() else {
+        return;
+    };
+    commands.entity(container).despawn_descendants().with_children(|parent| {
+        for path in &prefabs.files {
+            let name = path.rsplit('/').next().unwrap_or(path).trim_end_matches(".prefab.json");
+            parent.spawn((
+                Button,
+                Node {
+                    padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                    margin: UiRect::all(Val::Px(THUMBNAIL_MARGIN)),
+                    ..default()
+                },
+                BackgroundColor(BUTTON_COLOR),
+                PrefabButton(path.clone()),
+            )).with_child((
+                Text::new(name),
+                TextFont {
+                    font_size: 10.0,
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+fn rebuild_palette_tiles(
+    mut commands: Commands,
+    tilesets: Res<TilesetRegistry>,
+    palette: Res<TilePalette>,
+    q_container: Query<Entity, With<PaletteTiles>>,
+    mut q_scroll: Query<&mut ScrollPosition, With<PaletteScroll>>,
+) {
+    if !palette.is_changed() && !tilesets.is_changed() {
+        return;
+    }
+    let Ok(container) = q_container.get_single() else {
+        return;
+    };
+    commands.entity(container).despawn_descendants();
+    for mut scroll in q_scroll.iter_mut() {
+        scroll.offset_y = 0.0;
+    }
+
+    let Some(definition) = tilesets.manifest.tilesets.get(palette.tileset) else {
+        return;
+    };
+    let Some(tileset) = tilesets.get(&definition.name) else {
+        return;
+    };
+    commands.entity(container).with_children(|parent| {
+        for tile in 0..definition.tile_count() {
+            parent.spawn((
+                Button,
+                Node {
+                    width: Val::Px(THUMBNAIL_SIZE + 4.0),
+                    height: Val::Px(THUMBNAIL_SIZE + 4.0),
+                    margin: UiRect::all(Val::Px(THUMBNAIL_MARGIN)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BorderColor(Color::NONE),
+                PaletteTile(tile),
+            )).with_child((
+                ImageNode::from_atlas_image(
+                    tileset.image.clone(),
+                    TextureAtlas {
+                        layout: tileset.layout.clone(),
+                        index: tile,
+                    },
+                ),
+                Node {
+                    width: Val::Px(THUMBNAIL_SIZE),
+                    height: Val::Px(THUMBNAIL_SIZE),
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+fn update_palette_highlights(
+    state: Res<EditorState>,
+    palette: Res<TilePalette>,
+    tilesets: Res<TilesetRegistry>,
+    mut q_tiles: Query<(&PaletteTile, &mut BorderColor)>,
+    mut q_buttons: Query<(&PaletteButton, &mut BackgroundColor)>,
+    q_added: Query<(), Added<PaletteTile>>,
+) {
+    if !state.is_changed() && !palette.is_changed() && q_added.is_empty() {
+        return;
+    }
+    let texture = tilesets.manifest.tilesets.get(palette.tileset).map(|tileset| tileset.name.as_str());
+    for (PaletteTile(tile), mut border) in q_tiles.iter_mut() {
+        let selected = state.brush.as_ref().is_some_and(|brush| Some(brush.texture.as_str()) == texture && brush.tile == *tile);
+        border.0 = if selected { SELECTED_TILE_COLOR } else { Color::NONE };
+    }
+    for (button, mut background) in q_buttons.iter_mut() {
+        let active = *button == PaletteButton::Layer(state.layer) || *button == PaletteButton::Tool(state.tool);
+        background.0 = if active { ACTIVE_BUTTON_COLOR } else { BUTTON_COLOR };
+    }
+}
+
+fn update_palette_text(
+    state: Res<EditorState>,
+    palette: Res<TilePalette>,
+    tilesets: Res<TilesetRegistry>,
+    mut q_tileset_text: Query<&mut Text, (With<PaletteTilesetText>, Without<PaletteSelectionText>)>,
+    mut q_selection_text: Query<&mut Text, With<PaletteSelectionText>>,
+) {
+    if !state.is_changed() && !palette.is_changed() {
+        return;
+    }
+    let manifest = &tilesets.manifest;
+    let name = manifest.tilesets.get(palette.tileset).map_or("no tilesets", |tileset| tileset.name.as_str());
+    for mut text in q_tileset_text.iter_mut() {
+        **text = format!(" {} ({}/{}) ", name, palette.tileset + 1, manifest.tilesets.len());
+    }
+
+    let selection = match &state.brush {
+        Some(brush) => {
+            let collision = manifest.tile_properties(&brush.texture, brush.tile).with_overrides(&brush.overrides).collision;
+            format!("Selected: {} #{}\nCollision: {:?}", brush.texture, brush.tile, collision)
+        }
+        None => String::from("Selected: none"),
+    };
+    for mut text in q_selection_text.iter_mut() {
+        **text = selection.clone();
+    }
+}
+
+fn scroll_palette(
+    mut wheel_events: EventReader<MouseWheel>,
+    mut q_scroll: Query<(&RelativeCursorPosition, &mut ScrollPosition), With<PaletteScroll>>,
+) {
+    for event in wheel_events.read() {
+        let delta = match event.unit {
+            MouseScrollUnit::Line => event.y * (THUMBNAIL_SIZE + 2.0 * THUMBNAIL_MARGIN + 4.0),
+            MouseScrollUnit::Pixel => event.y,
+        };
+        for (cursor, mut scroll) in q_scroll.iter_mut() {
+            if cursor.mouse_over() {
+                scroll.offset_y = (scroll.offset_y - delta).max(0.0);
+            }
+        }
+    }
+}
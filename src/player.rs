@@ -3,13 +3,16 @@ use std::time::Duration;
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 use crate::layers::{PLAYER_LAYER};
+use crate::map::{LoadedMap, Map};
+use crate::resolution::Resolution;
+use crate::tileset::{TilesetManifest, TilesetRegistry};
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(Startup, setup_player)
-            .add_systems(Update, (handle_player_movement, get_next_animation, execute_animations, camera_follow_player));
+            .add_systems(Update, (handle_player_movement.run_if(resource_exists::<LoadedMap>.and(resource_exists::<TilesetRegistry>)), get_next_animation, execute_animations, camera_follow_player));
     }
 }
 
@@ -66,16 +69,18 @@ fn setup_player(
                 index: animation_config.animations[&animation_config.current_state].0,
             },
         ),
-        Transform::from_scale(Vec3::new(resolution.pixel_ratio as f32 * invert, resolution.pixel_ratio as f32, 1.0)).with_translation(Vec3::new(0.0, 0.0, 100.0)),
+        Transform::from_scale(Vec3::new(resolution.pixel_ratio as f32 * invert, resolution.pixel_ratio as f32, 1.0)).with_translation(Vec3::new(0.0, 0.0, PLAYER_Z)),
         animation_config,
         PlayerSprite,
         RenderLayers::layer(PLAYER_LAYER)
     ));
 }
 
+type AnimationFrames = (usize, usize, u8, Handle<Image>, Handle<TextureAtlasLayout>, bool);
+
 #[derive(Component)]
 struct AnimationConfig {
-    animations: HashMap<PlayerAnimationState, (usize, usize, u8, Handle<Image>, Handle<TextureAtlasLayout>, bool)>,
+    animations: HashMap<PlayerAnimationState, AnimationFrames>,
     current_state: PlayerAnimationState,
     frame_timer: Timer,
 }
@@ -96,7 +101,7 @@ enum PlayerAnimationState {
 }
 
 impl AnimationConfig {
-    fn new(initial_state: PlayerAnimationState, animations: HashMap<PlayerAnimationState, (usize, usize, u8, Handle<Image>, Handle<TextureAtlasLayout>, bool )> ) -> Self {
+    fn new(initial_state: PlayerAnimationState, animations: HashMap<PlayerAnimationState, AnimationFrames>) -> Self {
         Self {
             animations: animations.clone(),
             current_state: initial_state.clone(),
@@ -116,7 +121,7 @@ impl AnimationConfig {
         Timer::new(Duration::from_secs_f32(1.0 / (fps as f32)), TimerMode::Once)
     }
 
-    fn get_current_frame_range(&self) -> (usize, usize, u8, Handle<Image>, Handle<TextureAtlasLayout>, bool) {
+    fn get_current_frame_range(&self) -> AnimationFrames {
         self.animations[&self.current_state].clone()
     }
 }
@@ -128,9 +133,7 @@ fn execute_animations(time: Res<Time>, mut query: Query<(&mut AnimationConfig, &
         if config.frame_timer.just_finished() {
             if let Some(atlas) = &mut sprite.texture_atlas {
                 let (first, last, _,_,_,_) = config.get_current_frame_range();
-                if atlas.index == last {
-                    atlas.index = first;
-                } else if atlas.index < first || atlas.index > last {
+                if atlas.index >= last || atlas.index < first {
                     atlas.index = first;
                 } else {
                     atlas.index += 1;
@@ -143,14 +146,23 @@ fn execute_animations(time: Res<Time>, mut query: Query<(&mut AnimationConfig, &
 }
 
 const SPEED: f32 = 300.0;
-const MOVEMENT_DELAY: f32 = 0.08;
-
+pub const MOVEMENT_DELAY: f32 = 0.08;
+// Depth of the player sprite. Ground layers draw below it and the overlay layers above it.
+pub const PLAYER_Z: f32 = 100.0;
+// Half size of the box the player collides with, in frame pixels around the sprite centre. It is a
+// little smaller than a block, so the player fits through gaps one block wide.
+const COLLISION_HALF_SIZE: Vec2 = Vec2::new(6.0, 6.0);
+
+#[allow(clippy::too_many_arguments)]
 fn handle_player_movement(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut query: Query<(&mut Transform, &PlayerSprite)>,
     time: Res<Time>,
     mut last_movement: Local<f32>,
     mut is_moving: Local<bool>,
+    loaded_map: Res<LoadedMap>,
+    tilesets: Res<TilesetRegistry>,
+    resolution: Res<Resolution>,
 ) {
     let current_time = time.elapsed_secs();
     if !should_player_move(current_time, *last_movement, *is_moving) {
@@ -158,46 +170,74 @@ fn handle_player_movement(
     }
 
     for (mut transform, _) in query.iter_mut() {
-        *is_moving = move_player(&keyboard_input, &mut transform, time.delta_secs());
+        *is_moving = move_player(&keyboard_input, &mut transform, time.delta_secs(), &loaded_map.map, &tilesets.manifest, &resolution);
     }
 
     *last_movement = current_time;
 }
 
-fn should_player_move(current_time: f32, last_movement: f32, is_moving: bool) -> bool {
+pub fn should_player_move(current_time: f32, last_movement: f32, is_moving: bool) -> bool {
     current_time - last_movement >= MOVEMENT_DELAY || is_moving
 }
 
-fn move_player(keyboard_input: &Res<ButtonInput<KeyCode>>, transform: &mut Transform, delta_secs: f32) -> bool {
-    let mut moved = false;
-
+// WASD direction, ignored while Ctrl is held so shortcuts such as Ctrl+S don't walk the player.
+pub fn movement_direction(keyboard_input: &ButtonInput<KeyCode>) -> Option<Vec2> {
+    if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return None;
+    }
     if keyboard_input.pressed(KeyCode::KeyW) {
-        transform.translation.y += SPEED * delta_secs;
-        moved = true;
+        Some(Vec2::Y)
     } else if keyboard_input.pressed(KeyCode::KeyA) {
-        transform.translation.x -= SPEED * delta_secs;
-        moved = true;
+        Some(Vec2::NEG_X)
     } else if keyboard_input.pressed(KeyCode::KeyS) {
-        transform.translation.y -= SPEED * delta_secs;
-        moved = true;
+        Some(Vec2::NEG_Y)
     } else if keyboard_input.pressed(KeyCode::KeyD) {
-        transform.translation.x += SPEED * delta_secs;
-        moved = true;
+        Some(Vec2::X)
+    } else {
+        None
     }
+}
+
+pub fn move_player(keyboard_input: &ButtonInput<KeyCode>, transform: &mut Transform, delta_secs: f32, map: &Map, tilesets: &TilesetManifest, resolution: &Resolution) -> bool {
+    let Some(direction) = movement_direction(keyboard_input) else {
+        return false;
+    };
+
+    let position = transform.translation.truncate();
+    let speed = match map.properties_at_world(position, tilesets, resolution) {
+        Some(properties) => SPEED * properties.collision.speed_multiplier() / properties.movement_cost,
+        None => SPEED,
+    };
+    let target = position + direction * speed * delta_secs;
+
+    let walkable = collision_corners(target, resolution).into_iter().all(|corner| {
+        map.properties_at_world(corner, tilesets, resolution).is_some_and(|properties| !properties.collision.blocks_walking())
+    });
+    if !walkable {
+        return false;
+    }
+    transform.translation.x = target.x;
+    transform.translation.y = target.y;
+    true
+}
 
-    moved
+// Corners of the player's collision box at a world position. The box is smaller than a block, so its
+// corners touch every block it overlaps.
+pub fn collision_corners(position: Vec2, resolution: &Resolution) -> [Vec2; 4] {
+    let half = COLLISION_HALF_SIZE * resolution.pixel_ratio as f32;
+    [position - half, position + Vec2::new(half.x, -half.y), position + Vec2::new(-half.x, half.y), position + half]
 }
 fn get_next_animation(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut query: Query<&mut AnimationConfig>,
 ) {
     for mut animation in query.iter_mut() {
-        let new_state = match () {
-            _ if keyboard_input.pressed(KeyCode::KeyW) => PlayerAnimationState::WalkForward,
-            _ if keyboard_input.pressed(KeyCode::KeyA) => PlayerAnimationState::WalkLeft,
-            _ if keyboard_input.pressed(KeyCode::KeyS) => PlayerAnimationState::WalkBackward,
-            _ if keyboard_input.pressed(KeyCode::KeyD) => PlayerAnimationState::WalkRight,
-            _ => match animation.current_state {
+        let new_state = match movement_direction(&keyboard_input) {
+            Some(Vec2::Y) => PlayerAnimationState::WalkForward,
+            Some(Vec2::NEG_X) => PlayerAnimationState::WalkLeft,
+            Some(Vec2::NEG_Y) => PlayerAnimationState::WalkBackward,
+            Some(_) => PlayerAnimationState::WalkRight,
+            None => match animation.current_state {
                 PlayerAnimationState::WalkForward => PlayerAnimationState::IdleForward,
                 PlayerAnimationState::WalkLeft => PlayerAnimationState::IdleLeft,
                 PlayerAnimationState::WalkBackward => PlayerAnimationState::Idle,
@@ -27,7 +27,7 @@ fn setup_resolution(mut commands: Commands,window_query: Query<&Window>){
         screen_dimensions: Vec2::new(window.width(), window.height()),
         pixel_ratio,
         frame_size: UVec2::new(frame_size_x, frame_size_x),
-        map_translation: Vec2::new(map_translation_x as f32, map_translation_x as f32 * -1.0),
+        map_translation: Vec2::new(map_translation_x as f32, -(map_translation_x as f32)),
     });
 
 }
\ No newline at end of file
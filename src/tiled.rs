@@ -0,0 +1,580 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use bevy::reflect::erased_serde::__private::serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::map::{Block, Collision, Map, MapError, MapLayer};
+use crate::tileset::{TileOverrides, TilesetManifest};
+
+const FLIP_FLAGS: u32 = 0xF000_0000;
+const COLLISION_FOR_PROPERTY: &str = "collision_for";
+const MAP_LAYER_PROPERTY: &str = "map_layer";
+pub const COLLISION_TILESET: &str = "collision";
+pub const COLLISION_TILESET_IMAGE: &str = "editor/collision.png";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TiledProperty {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    pub value: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TiledTile {
+    pub id: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<TiledProperty>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TiledTileset {
+    pub firstgid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imagewidth: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imageheight: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilewidth: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tileheight: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilecount: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiles: Vec<TiledTile>,
+}
+
+impl TiledTileset {
+    fn texture_name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        // External tilesets are referenced by file, so the file stem stands in for the tileset name.
+        let source = self.source.as_deref().unwrap_or_default();
+        let file = source.rsplit(['/', '\\']).next().unwrap_or(source);
+        file.split('.').next().unwrap_or(file).to_string()
+    }
+
+    fn tile_collision(&self, tile: usize) -> Option<Collision> {
+        let tile = self.tiles.iter().find(|t| t.id == tile)?;
+        collision_property(&tile.properties)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TiledLayer {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: String,
+    #[serde(default)]
+    pub width: usize,
+    #[serde(default)]
+    pub height: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<TiledProperty>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<TiledLayer>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offsetx: f32,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offsety: f32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TiledMap {
+    #[serde(rename = "type", default = "default_map_type")]
+    pub kind: String,
+    pub width: usize,
+    pub height: usize,
+    pub tilewidth: u32,
+    pub tileheight: u32,
+    #[serde(default)]
+    pub infinite: bool,
+    #[serde(default = "default_orientation")]
+    pub orientation: String,
+    pub layers: Vec<TiledLayer>,
+    pub tilesets: Vec<TiledTileset>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<TiledProperty>,
+}
+
+fn default_map_type() -> String {
+    String::from("map")
+}
+
+fn is_zero(value: &f32) -> bool {
+    *value == 0.0
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn default_orientation() -> String {
+    String::from("orthogonal")
+}
+
+fn is_collision_property(property: &TiledProperty) -> bool {
+    property.name.eq_ignore_ascii_case("collision")
+}
+
+fn collision_property(properties: &[TiledProperty]) -> Option<Collision> {
+    let value = properties.iter().find(|property| is_collision_property(property))?;
+    value.value.as_str().and_then(Collision::from_name)
+}
+
+// A collision property whose value names no collision, which the importer cannot use.
+fn unknown_collision(properties: &[TiledProperty]) -> Option<&Value> {
+    properties.iter()
+        .find(|property| is_collision_property(property))
+        .map(|property| &property.value)
+        .filter(|value| value.as_str().and_then(Collision::from_name).is_none())
+}
+
+fn string_property<'a>(properties: &'a [TiledProperty], name: &str) -> Option<&'a str> {
+    properties.iter().find(|property| property.name == name)?.value.as_str()
+}
+
+pub struct TiledImportOptions {
+    pub chunk_size: usize,
+    // Used for Base cells that are empty in every Tiled layer mapped onto Base.
+    pub fill: Block,
+}
+
+pub struct TiledImport {
+    pub map: Map,
+    pub warnings: Vec<String>,
+}
+
+pub fn import_tiled_json(contents: &str, options: &TiledImportOptions) -> Result<TiledImport, MapError> {
+    let tiled: TiledMap = serde_json::from_str(contents)?;
+    import_tiled(&tiled, options)
+}
+
+pub fn import_tiled_file(path: &str, options: &TiledImportOptions) -> Result<TiledImport, MapError> {
+    if path.ends_with(".tmx") {
+        return Err(MapError::Import(String::from("TMX (XML) maps are not supported, export the map from Tiled as JSON (.tmj)")));
+    }
+    import_tiled_json(&std::fs::read_to_string(path)?, options)
+}
+
+pub fn import_tiled(tiled: &TiledMap, options: &TiledImportOptions) -> Result<TiledImport, MapError> {
+    if tiled.infinite {
+        return Err(MapError::Import(String::from("infinite Tiled maps are not supported")));
+    }
+    if options.chunk_size == 0 {
+        return Err(MapError::Import(String::from("chunk size must be greater than zero")));
+    }
+
+    let mut warnings = Vec::new();
+    if tiled.orientation != "orthogonal" {
+        warnings.push(format!("{} orientation is imported as orthogonal", tiled.orientation));
+    }
+    if !tiled.properties.is_empty() {
+        warnings.push(String::from("map properties are not imported"));
+    }
+
+    let mut tilesets = tiled.tilesets.clone();
+    tilesets.sort_by_key(|tileset| tileset.firstgid);
+    for tileset in &tilesets {
+        warn_about_tileset(tileset, &mut warnings);
+    }
+
+    let chunks_wide = tiled.width.div_ceil(options.chunk_size);
+    let chunks_high = tiled.height.div_ceil(options.chunk_size);
+    let width = chunks_wide * options.chunk_size;
+    let height = chunks_high * options.chunk_size;
+    let mut grids: BTreeMap<MapLayer, Vec<Vec<Option<Block>>>> = BTreeMap::new();
+
+    let mut layers: Vec<&TiledLayer> = Vec::new();
+    flatten_layers(&tiled.layers, &mut layers, &mut warnings);
+    for layer in &layers {
+        let known = |property: &&TiledProperty| is_collision_property(property) || [MAP_LAYER_PROPERTY, COLLISION_FOR_PROPERTY].contains(&property.name.as_str());
+        for property in layer.properties.iter().filter(|property| !known(property)) {
+            warnings.push(format!("property \"{}\" of layer \"{}\" is not imported", property.name, layer.name));
+        }
+        if let Some(value) = unknown_collision(&layer.properties) {
+            warnings.push(format!("collision {} of layer \"{}\" is not a known collision and was ignored", value, layer.name));
+        }
+    }
+    let (collision_layers, layers): (Vec<&TiledLayer>, Vec<&TiledLayer>) = layers.into_iter()
+        .partition(|layer| string_property(&layer.properties, COLLISION_FOR_PROPERTY).is_some());
+    for layer in layers {
+        let Some(map_layer) = string_property(&layer.properties, MAP_LAYER_PROPERTY)
+            .and_then(MapLayer::from_name)
+            .or_else(|| MapLayer::from_name(&layer.name))
+        else {
+            warnings.push(format!("tile layer \"{}\" does not match a map layer and was skipped", layer.name));
+            continue;
+        };
+        if layer.offsetx != 0.0 || layer.offsety != 0.0 {
+            warnings.push(format!("offset of layer \"{}\" is ignored", layer.name));
+        }
+        if layer.opacity != 1.0 || !layer.visible {
+            warnings.push(format!("opacity and visibility of layer \"{}\" are ignored", layer.name));
+        }
+        if grids.contains_key(&map_layer) {
+            warnings.push(format!("layer \"{}\" is merged into the {:?} layer", layer.name, map_layer));
+        }
+
+        let data = layer_data(layer)?;
+        let layer_collision = collision_property(&layer.properties);
+        let grid = grids.entry(map_layer).or_insert_with(|| vec![vec![None; width]; height]);
+        let mut flipped = 0;
+        for (index, raw_gid) in data.iter().enumerate() {
+            let gid = raw_gid & !FLIP_FLAGS;
+            if gid == 0 {
+                continue;
+            }
+            if raw_gid & FLIP_FLAGS != 0 {
+                flipped += 1;
+            }
+            let (tileset, tile) = resolve_gid(&tilesets, gid, layer)?;
+            let collision = layer_collision.or_else(|| tileset.tile_collision(tile));
+            let (x, y) = (index % layer.width, index / layer.width);
+            if y < height && x < width {
+                grid[y][x] = Some(Block {
+                    tile,
+                    texture: tileset.texture_name(),
+                    overrides: TileOverrides { collision, ..Default::default() },
+                });
+            }
+        }
+        if flipped > 0 {
+            warnings.push(format!("{} flipped or rotated tiles in layer \"{}\" were imported unflipped", flipped, layer.name));
+        }
+    }
+
+    for layer in collision_layers {
+        apply_collision_layer(layer, &tilesets, &mut grids, &mut warnings)?;
+    }
+
+    let empty_base = grids.get(&MapLayer::Base)
+        .map_or(width * height, |base| base.iter().flatten().filter(|cell| cell.is_none()).count());
+    if empty_base > 0 {
+        warnings.push(format!("{} empty Base cells were filled with tile {} of \"{}\"", empty_base, options.fill.tile, options.fill.texture));
+    }
+
+    Ok(TiledImport { map: Map::from_layer_grids(options.chunk_size, width, height, &grids, &options.fill), warnings })
+}
+
+// Reports tile data of a tileset the importer cannot use: properties of external tilesets, which live
+// in a file next to the map, and tile properties other than collision.
+fn warn_about_tileset(tileset: &TiledTileset, warnings: &mut Vec<String>) {
+    let name = tileset.texture_name();
+    if let Some(source) = &tileset.source {
+        warnings.push(format!("tile properties of external tileset \"{}\" are not imported, embed the tileset to import its collisions", source));
+    }
+
+    let mut ignored: Vec<&str> = tileset.tiles.iter()
+        .flat_map(|tile| &tile.properties)
+        .filter(|property| !is_collision_property(property))
+        .map(|property| property.name.as_str())
+        .collect();
+    ignored.sort_unstable();
+    ignored.dedup();
+    if !ignored.is_empty() {
+        warnings.push(format!("tile properties {:?} of tileset \"{}\" are not imported", ignored, name));
+    }
+
+    let unknown = tileset.tiles.iter().filter(|tile| unknown_collision(&tile.properties).is_some()).count();
+    if unknown > 0 {
+        warnings.push(format!("{} tiles of tileset \"{}\" have an unknown collision and were imported without one", unknown, name));
+    }
+}
+
+fn resolve_gid<'a>(tilesets: &'a [TiledTileset], gid: u32, layer: &TiledLayer) -> Result<(&'a TiledTileset, usize), MapError> {
+    let Some(tileset) = tilesets.iter().rev().find(|tileset| tileset.firstgid <= gid) else {
+        return Err(MapError::Import(format!("tile id {} in layer \"{}\" has no tileset", gid, layer.name)));
+    };
+    Ok((tileset, (gid - tileset.firstgid) as usize))
+}
+
+fn apply_collision_layer(
+    layer: &TiledLayer,
+    tilesets: &[TiledTileset],
+    grids: &mut BTreeMap<MapLayer, Vec<Vec<Option<Block>>>>,
+    warnings: &mut Vec<String>,
+) -> Result<(), MapError> {
+    let target = string_property(&layer.properties, COLLISION_FOR_PROPERTY).unwrap_or_default();
+    let Some(grid) = MapLayer::from_name(target).and_then(|map_layer| grids.get_mut(&map_layer)) else {
+        warnings.push(format!("collision layer \"{}\" refers to missing layer \"{}\" and was skipped", layer.name, target));
+        return Ok(());
+    };
+
+    let (mut unknown, mut uncovered) = (0, 0);
+    for (index, raw_gid) in layer_data(layer)?.iter().enumerate() {
+        let gid = raw_gid & !FLIP_FLAGS;
+        if gid == 0 {
+            continue;
+        }
+        let (tileset, tile) = resolve_gid(tilesets, gid, layer)?;
+        let Some(collision) = tileset.tile_collision(tile) else {
+            unknown += 1;
+            continue;
+        };
+        match grid.get_mut(index / layer.width).and_then(|row| row.get_mut(index % layer.width)) {
+            Some(Some(block)) => block.overrides.collision = Some(collision),
+            _ => uncovered += 1,
+        }
+    }
+    if unknown > 0 {
+        warnings.push(format!("{} tiles in collision layer \"{}\" have no collision property", unknown, layer.name));
+    }
+    if uncovered > 0 {
+        warnings.push(format!("{} collisions in layer \"{}\" are not above a tile", uncovered, layer.name));
+    }
+    Ok(())
+}
+
+fn flatten_layers<'a>(layers: &'a [TiledLayer], out: &mut Vec<&'a TiledLayer>, warnings: &mut Vec<String>) {
+    for layer in layers {
+        match layer.kind.as_str() {
+            "tilelayer" => out.push(layer),
+            "group" => flatten_layers(&layer.layers, out, warnings),
+            kind => warnings.push(format!("{} \"{}\" is not supported and was skipped", kind, layer.name)),
+        }
+    }
+}
+
+fn layer_data(layer: &TiledLayer) -> Result<Vec<u32>, MapError> {
+    if layer.encoding.as_deref().is_some_and(|encoding| encoding != "csv") {
+        return Err(MapError::Import(format!("layer \"{}\" uses base64 data, save the map with CSV layer format", layer.name)));
+    }
+    let data = layer.data.clone().unwrap_or(Value::Array(Vec::new()));
+    let data: Vec<u32> = serde_json::from_value(data)?;
+    if data.len() != layer.width * layer.height {
+        return Err(MapError::Import(format!("layer \"{}\" has {} tiles instead of {}x{}", layer.name, data.len(), layer.width, layer.height)));
+    }
+    Ok(data)
+}
+
+pub struct TiledExport {
+    pub map: TiledMap,
+    pub warnings: Vec<String>,
+}
+
+pub fn export_tiled(map: &Map, tilesets: &TilesetManifest) -> TiledExport {
+    let mut warnings = Vec::new();
+    let chunks_wide = map.chunks.iter().map(Vec::len).max().unwrap_or(0);
+    if map.chunks.iter().any(|row| row.len() != chunks_wide) {
+        warnings.push(String::from("chunk rows of different lengths are padded with empty tiles"));
+    }
+    let width = chunks_wide * map.chunk_size;
+    let height = map.chunks.len() * map.chunk_size;
+
+    let mut tile_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, _, _, block) in map_blocks(map) {
+        let count = tile_counts.entry(&block.texture).or_default();
+        *count = (*count).max(block.tile + 1);
+    }
+    let tile_size = tilesets.tilesets.first().map_or([16, 16], |tileset| tileset.tile_size);
+
+    let mut tiled_tilesets = Vec::new();
+    let mut firstgids = BTreeMap::new();
+    let mut next_gid = 1;
+    for (texture, used) in tile_counts {
+        let tileset = match tilesets.get(texture) {
+            Some(definition) => TiledTileset {
+                firstgid: next_gid,
+                name: Some(texture.to_string()),
+                image: Some(definition.image.clone()),
+                imagewidth: Some(definition.offset[0] * 2 + definition.columns * definition.tile_size[0] + definition.columns.saturating_sub(1) * definition.spacing[0]),
+                imageheight: Some(definition.offset[1] * 2 + definition.rows * definition.tile_size[1] + definition.rows.saturating_sub(1) * definition.spacing[1]),
+                tilewidth: Some(definition.tile_size[0]),
+                tileheight: Some(definition.tile_size[1]),
+                tilecount: Some(definition.tile_count().max(used) as u32),
+                columns: Some(definition.columns),
+                spacing: Some(definition.spacing[0]),
+                margin: Some(definition.offset[0]),
+                ..empty_tileset(next_gid)
+            },
+            None => {
+                warnings.push(format!("texture \"{}\" is not in the tileset manifest and is exported without an image", texture));
+                TiledTileset { name: Some(texture.to_string()), tilecount: Some(used as u32), ..empty_tileset(next_gid) }
+            }
+        };
+        firstgids.insert(texture, next_gid);
+        next_gid += tileset.tilecount.unwrap_or_default();
+        tiled_tilesets.push(tileset);
+    }
+    let collision_gid = next_gid;
+    tiled_tilesets.push(collision_tileset(collision_gid, tile_size));
+
+    let mut layers = Vec::new();
+    let mut lost_overrides = 0;
+    for layer in MapLayer::ALL {
+        let mut data = vec![0; width * height];
+        let mut collisions = vec![0; width * height];
+        for (block_layer, x, y, block) in map_blocks(map) {
+            if block_layer != layer {
+                continue;
+            }
+            data[y * width + x] = firstgids[block.texture.as_str()] + block.tile as u32;
+            if let Some(collision) = block.overrides.collision {
+                collisions[y * width + x] = collision_gid + Collision::ALL.iter().position(|c| *c == collision).unwrap_or_default() as u32;
+            }
+            let overrides = &block.overrides;
+            if overrides.movement_cost.is_some() || overrides.footstep_sound.is_some() || overrides.tags.is_some() {
+                lost_overrides += 1;
+            }
+        }
+
+        let name = format!("{:?}", layer);
+        if layer == MapLayer::Base || data.iter().any(|gid| *gid != 0) {
+            layers.push(tile_layer(layers.len() as u32 + 1, name.clone(), width, height, data, Vec::new()));
+        }
+        if collisions.iter().any(|gid| *gid != 0) {
+            let properties = vec![TiledProperty { name: COLLISION_FOR_PROPERTY.to_string(), kind: String::from("string"), value: Value::String(name.clone()) }];
+            layers.push(tile_layer(layers.len() as u32 + 1, format!("{} collision", name), width, height, collisions, properties));
+        }
+    }
+    if lost_overrides > 0 {
+        warnings.push(format!("{} blocks override movement cost, footstep sound or tags, which are not exported", lost_overrides));
+    }
+
+    let map = TiledMap {
+        kind: default_map_type(),
+        width,
+        height,
+        tilewidth: tile_size[0],
+        tileheight: tile_size[1],
+        infinite: false,
+        orientation: default_orientation(),
+        layers,
+        tilesets: tiled_tilesets,
+        properties: Vec::new(),
+    };
+    TiledExport { map, warnings }
+}
+
+// Tiled resolves tileset images relative to the map file, so the image paths, which export_tiled
+// leaves relative to assets_root, are rewritten relative to the directory the map is written to.
+pub fn export_tiled_file(map: &Map, tilesets: &TilesetManifest, assets_root: &str, path: &str) -> Result<Vec<String>, MapError> {
+    let mut export = export_tiled(map, tilesets);
+    let output_dir = std::path::absolute(path)?.parent().map(Path::to_path_buf).unwrap_or_default();
+    let assets_root = std::path::absolute(assets_root)?;
+    for tileset in &mut export.map.tilesets {
+        if let Some(image) = &mut tileset.image {
+            *image = relative_path(&output_dir, &assets_root.join(&*image)).to_string_lossy().replace('\\', "/");
+        }
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&export.map)?)?;
+    Ok(export.warnings)
+}
+
+fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+    let (from, to) = (normalized_components(from_dir), normalized_components(to));
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut relative: PathBuf = from[common..].iter().map(|_| Component::ParentDir).collect();
+    relative.extend(&to[common..]);
+    relative
+}
+
+fn normalized_components(path: &Path) -> Vec<Component<'_>> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(components.last(), Some(Component::Normal(_))) => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    components
+}
+
+// Every block of the map as (layer, x, y, block) in map-wide block coordinates.
+fn map_blocks(map: &Map) -> impl Iterator<Item = (MapLayer, usize, usize, &Block)> {
+    map.chunks.iter().enumerate().flat_map(move |(chunk_y, row)| {
+        row.iter().enumerate().flat_map(move |(chunk_x, chunk)| {
+            MapLayer::ALL.into_iter().flat_map(move |layer| {
+                chunk.layer_blocks(layer).into_iter().map(move |(i, j, block)| {
+                    (layer, chunk_x * map.chunk_size + j, chunk_y * map.chunk_size + i, block)
+                })
+            })
+        })
+    })
+}
+
+fn empty_tileset(firstgid: u32) -> TiledTileset {
+    TiledTileset {
+        firstgid,
+        name: None,
+        source: None,
+        image: None,
+        imagewidth: None,
+        imageheight: None,
+        tilewidth: None,
+        tileheight: None,
+        tilecount: None,
+        columns: None,
+        spacing: None,
+        margin: None,
+        tiles: Vec::new(),
+    }
+}
+
+fn collision_tileset(firstgid: u32, tile_size: [u32; 2]) -> TiledTileset {
+    let tiles = Collision::ALL.iter().enumerate().map(|(id, collision)| TiledTile {
+        id,
+        properties: vec![TiledProperty { name: String::from("collision"), kind: String::from("string"), value: Value::String(format!("{:?}", collision)) }],
+    }).collect();
+    TiledTileset {
+        name: Some(COLLISION_TILESET.to_string()),
+        image: Some(COLLISION_TILESET_IMAGE.to_string()),
+        imagewidth: Some(tile_size[0] * Collision::ALL.len() as u32),
+        imageheight: Some(tile_size[1]),
+        tilewidth: Some(tile_size[0]),
+        tileheight: Some(tile_size[1]),
+        tilecount: Some(Collision::ALL.len() as u32),
+        columns: Some(Collision::ALL.len() as u32),
+        tiles,
+        ..empty_tileset(firstgid)
+    }
+}
+
+fn tile_layer(id: u32, name: String, width: usize, height: usize, data: Vec<u32>, properties: Vec<TiledProperty>) -> TiledLayer {
+    TiledLayer {
+        id: Some(id),
+        kind: String::from("tilelayer"),
+        name,
+        width,
+        height,
+        data: Some(Value::from(data)),
+        encoding: None,
+        properties,
+        layers: Vec::new(),
+        offsetx: 0.0,
+        offsety: 0.0,
+        opacity: 1.0,
+        visible: true,
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use bevy::asset::AssetLoadFailedEvent;
+use bevy::prelude::*;
+use bevy::reflect::erased_serde::__private::serde::{Deserialize, Serialize};
+use crate::autotile::AutotileRule;
+use crate::map::{Collision, MapError};
+use crate::tileset_loader::TilesetManifestLoader;
+
+pub struct TilesetPlugin;
+
+impl Plugin for TilesetPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<TilesetManifest>()
+            .init_asset_loader::<TilesetManifestLoader>()
+            .add_systems(Startup, setup_tilesets)
+            .add_systems(Update, apply_loaded_tilesets);
+    }
+}
+
+pub const TILESET_MANIFEST_PATH: &str = "tilesets.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TileProperties {
+    #[serde(default)]
+    pub collision: Collision,
+    #[serde(default = "default_movement_cost")]
+    pub movement_cost: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footstep_sound: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+fn default_movement_cost() -> f32 {
+    1.0
+}
+
+// Player speed is divided by the movement cost, so it has to be finite and above zero.
+pub fn valid_movement_cost(cost: f32) -> bool {
+    cost.is_finite() && cost > 0.0
+}
+
+impl Default for TileProperties {
+    fn default() -> Self {
+        Self {
+            collision: Collision::default(),
+            movement_cost: default_movement_cost(),
+            footstep_sound: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl TileProperties {
+    pub fn with_overrides(mut self, overrides: &TileOverrides) -> Self {
+        if let Some(collision) = overrides.collision {
+            self.collision = collision;
+        }
+        if let Some(movement_cost) = overrides.movement_cost {
+            self.movement_cost = movement_cost;
+        }
+        if let Some(footstep_sound) = &overrides.footstep_sound {
+            self.footstep_sound = Some(footstep_sound.clone());
+        }
+        if let Some(tags) = &overrides.tags {
+            self.tags = tags.clone();
+        }
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TileOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collision: Option<Collision>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub movement_cost: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footstep_sound: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+impl TileOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == TileOverrides::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TilesetDefinition {
+    pub name: String,
+    pub image: String,
+    pub tile_size: [u32; 2],
+    pub columns: u32,
+    pub rows: u32,
+    #[serde(default)]
+    pub spacing: [u32; 2],
+    #[serde(default)]
+    pub offset: [u32; 2],
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tiles: HashMap<usize, TileProperties>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub autotiles: HashMap<String, AutotileRule>,
+}
+
+impl TilesetDefinition {
+    pub fn tile_count(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+
+    pub fn atlas_layout(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(
+            UVec2::from(self.tile_size),
+            self.columns,
+            self.rows,
+            Some(UVec2::from(self.spacing)),
+            Some(UVec2::from(self.offset)),
+        )
+    }
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TilesetManifest {
+    pub tilesets: Vec<TilesetDefinition>,
+}
+
+impl TilesetManifest {
+    pub fn load_from_json(path: &str) -> Result<Self, MapError> {
+        let mut file = File::open(path)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        Self::from_json_slice(&contents)
+    }
+
+    pub fn from_json_slice(contents: &[u8]) -> Result<Self, MapError> {
+        let manifest: TilesetManifest = serde_json::from_slice(contents)?;
+        manifest.validate()?;
+
+        Ok(manifest)
+    }
+
+    pub fn validate(&self) -> Result<(), MapError> {
+        for tileset in &self.tilesets {
+            for (tile, properties) in &tileset.tiles {
+                if !valid_movement_cost(properties.movement_cost) {
+                    return Err(MapError::InvalidMovementCost {
+                        cost: properties.movement_cost,
+                        texture: tileset.name.clone(),
+                        tile: *tile,
+                        location: None,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn tile_counts(&self) -> HashMap<String, usize> {
+        self.tilesets.iter().map(|tileset| (tileset.name.clone(), tileset.tile_count())).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TilesetDefinition> {
+        self.tilesets.iter().find(|tileset| tileset.name == name)
+    }
+
+    pub fn autotile_rule(&self, texture: &str, terrain: &str) -> Option<&AutotileRule> {
+        self.get(texture)?.autotiles.get(terrain)
+    }
+
+    pub fn autotile_rule_for_tile(&self, texture: &str, tile: usize) -> Option<&AutotileRule> {
+        let mut rules: Vec<(&String, &AutotileRule)> = self.get(texture)?.autotiles.iter().collect();
+        rules.sort_by_key(|(terrain, _)| *terrain);
+        rules.into_iter().map(|(_, rule)| rule).find(|rule| rule.contains_tile(tile))
+    }
+
+    pub fn tile_properties(&self, texture: &str, tile: usize) -> TileProperties {
+        self.get(texture)
+            .and_then(|tileset| tileset.tiles.get(&tile))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+pub struct Tileset {
+    pub definition: TilesetDefinition,
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
+#[derive(Resource, Default)]
+pub struct TilesetRegistry {
+    pub manifest: TilesetManifest,
+    tilesets: HashMap<String, Tileset>,
+}
+
+impl TilesetRegistry {
+    // Registry of a manifest without any loaded images. The map and editor systems only read the
+    // manifest, so this is enough to run them headless.
+    pub fn from_manifest(manifest: TilesetManifest) -> Self {
+        Self { manifest, tilesets: HashMap::new() }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tileset> {
+        self.tilesets.get(name)
+    }
+
+    pub fn tile_counts(&self) -> HashMap<String, usize> {
+        self.manifest.tile_counts()
+    }
+}
+
+#[derive(Resource)]
+pub struct TilesetManifestHandle(pub Handle<TilesetManifest>);
+
+fn setup_tilesets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TilesetManifestHandle(asset_server.load(TILESET_MANIFEST_PATH)));
+}
+
+// Rebuilds the registry whenever the manifest is loaded or edited on disk. Systems reading the
+// registry see it change and pick up the new tilesets.
+fn apply_loaded_tilesets(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<TilesetManifest>>,
+    mut failed_events: EventReader<AssetLoadFailedEvent<TilesetManifest>>,
+    manifests: Res<Assets<TilesetManifest>>,
+    manifest_handle: Res<TilesetManifestHandle>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    for event in failed_events.read() {
+        if event.id == manifest_handle.0.id() {
+            error!("Failed to load {}: {}", TILESET_MANIFEST_PATH, event.error);
+        }
+    }
+    let changed = asset_events.read().any(|event| match event {
+        AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id == manifest_handle.0.id(),
+        _ => false,
+    });
+    if !changed {
+        return;
+    }
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        return;
+    };
+
+    let mut registry = TilesetRegistry::default();
+    for definition in &manifest.tilesets {
+        let tileset = Tileset {
+            image: asset_server.load(definition.image.clone()),
+            layout: texture_atlas_layouts.add(definition.atlas_layout()),
+            definition: definition.clone(),
+        };
+        registry.tilesets.insert(definition.name.clone(), tileset);
+    }
+    registry.manifest = manifest.clone();
+
+    commands.insert_resource(registry);
+}
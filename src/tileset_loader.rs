@@ -0,0 +1,29 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use crate::map::MapError;
+use crate::tileset::TilesetManifest;
+
+#[derive(Default)]
+pub struct TilesetManifestLoader;
+
+impl AssetLoader for TilesetManifestLoader {
+    type Asset = TilesetManifest;
+    type Settings = ();
+    type Error = MapError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        TilesetManifest::from_json_slice(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tilesets.json"]
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::{HashSet, VecDeque};
+use bevy::reflect::erased_serde::__private::serde::{Deserialize, Serialize};
+use crate::editor::paint_block;
+use crate::history::BlockChange;
+use crate::map::{Block, Map, MapError, MapLayer};
+use crate::tileset::TilesetManifest;
+
+// Flood fills larger than this are refused instead of running across the whole map.
+pub const FLOOD_FILL_LIMIT: usize = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EditorTool {
+    #[default]
+    Brush,
+    Rectangle,
+    Fill,
+    Line,
+    Stamp,
+    Eyedropper,
+    Select,
+    Collision,
+}
+
+impl EditorTool {
+    pub const ALL: [EditorTool; 8] = [
+        EditorTool::Brush,
+        EditorTool::Rectangle,
+        EditorTool::Fill,
+        EditorTool::Line,
+        EditorTool::Stamp,
+        EditorTool::Eyedropper,
+        EditorTool::Select,
+        EditorTool::Collision,
+    ];
+}
+
+// A rectangle of blocks copied from one layer. Empty cells are transparent when stamped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Stamp {
+    pub blocks: Vec<Vec<Option<Block>>>,
+}
+
+impl Stamp {
+    pub fn copy(map: &Map, from: (isize, isize), to: (isize, isize), layer: MapLayer) -> Self {
+        let (min_x, max_x) = (from.0.min(to.0), from.0.max(to.0));
+        let (min_y, max_y) = (from.1.min(to.1), from.1.max(to.1));
+        let blocks = (min_y..=max_y)
+            .map(|y| (min_x..=max_x).map(|x| map.layer_block_at(x, y, layer).cloned()).collect())
+            .collect();
+        Self { blocks }
+    }
+
+    pub fn width(&self) -> usize {
+        self.blocks.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn save_to_json(&self, path: &str) -> Result<(), MapError> {
+        Ok(std::fs::write(path, serde_json::to_string_pretty(self)?)?)
+    }
+
+    pub fn load_from_json(path: &str) -> Result<Self, MapError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+pub const PREFAB_DIR: &str = "assets/prefabs";
+const PREFAB_EXTENSION: &str = ".prefab.json";
+
+// Prefab files in a directory, sorted by name. A missing directory simply has no prefabs.
+pub fn list_prefabs(dir: &str) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut prefabs: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(PREFAB_EXTENSION))
+        .map(|name| format!("{}/{}", dir, name))
+        .collect();
+    prefabs.sort();
+    prefabs
+}
+
+pub fn next_prefab_path(dir: &str) -> String {
+    let existing = list_prefabs(dir);
+    (1..).map(|n| format!("{}/prefab_{}{}", dir, n, PREFAB_EXTENSION))
+        .find(|path| !existing.contains(path))
+        .unwrap_or_default()
+}
+
+pub fn save_prefab(stamp: &Stamp, dir: &str) -> Result<String, MapError> {
+    std::fs::create_dir_all(dir)?;
+    let path = next_prefab_path(dir);
+    stamp.save_to_json(&path)?;
+    Ok(path)
+}
+
+pub fn rectangle_cells(from: (isize, isize), to: (isize, isize)) -> Vec<(isize, isize)> {
+    let (min_x, max_x) = (from.0.min(to.0), from.0.max(to.0));
+    let (min_y, max_y) = (from.1.min(to.1), from.1.max(to.1));
+    (min_y..=max_y).flat_map(|y| (min_x..=max_x).map(move |x| (x, y))).collect()
+}
+
+// Bresenham line including both end points.
+pub fn line_cells(from: (isize, isize), to: (isize, isize)) -> Vec<(isize, isize)> {
+    let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+    let (step_x, step_y) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let (mut x, mut y) = from;
+    let mut error = dx + dy;
+    let mut cells = vec![(x, y)];
+    while (x, y) != to {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+        cells.push((x, y));
+    }
+    cells
+}
+
+// The 4-connected region of blocks on a layer that show the same tile as the start block, across
+// chunk borders. Returns None when the region is larger than `limit`.
+pub fn flood_fill_cells(map: &Map, x: isize, y: isize, layer: MapLayer, limit: usize) -> Option<Vec<(isize, isize)>> {
+    // Every block of the map has a Base block, so that tells whether a position is inside the map.
+    let inside = |x: isize, y: isize| map.block_at(x, y).is_some();
+    if !inside(x, y) {
+        return Some(Vec::new());
+    }
+    let tile_at = |x: isize, y: isize| map.layer_block_at(x, y, layer).map(|block| (block.texture.as_str(), block.tile));
+    let target = tile_at(x, y);
+
+    let mut region = Vec::new();
+    let mut seen = HashSet::from([(x, y)]);
+    let mut queue = VecDeque::from([(x, y)]);
+    while let Some((cx, cy)) = queue.pop_front() {
+        region.push((cx, cy));
+        if region.len() > limit {
+            return None;
+        }
+        for (nx, ny) in [(cx, cy - 1), (cx + 1, cy), (cx, cy + 1), (cx - 1, cy)] {
+            if inside(nx, ny) && tile_at(nx, ny) == target && seen.insert((nx, ny)) {
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    Some(region)
+}
+
+pub fn paint_cells(
+    map: &mut Map,
+    cells: &[(isize, isize)],
+    layer: MapLayer,
+    block: Option<&Block>,
+    tilesets: &TilesetManifest,
+) -> Vec<BlockChange> {
+    cells.iter().flat_map(|(x, y)| paint_block(map, *x, *y, layer, block.cloned(), tilesets)).collect()
+}
+
+// Paints the stamp with its top-left block at (x, y).
+pub fn apply_stamp(map: &mut Map, x: isize, y: isize, layer: MapLayer, stamp: &Stamp, tilesets: &TilesetManifest) -> Vec<BlockChange> {
+    let mut changes = Vec::new();
+    for (i, row) in stamp.blocks.iter().enumerate() {
+        for (j, block) in row.iter().enumerate() {
+            if let Some(block) = block {
+                changes.extend(paint_block(map, x + j as isize, y + i as isize, layer, Some(block.clone()), tilesets));
+            }
+        }
+    }
+    changes
+}
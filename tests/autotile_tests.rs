@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use rpggame::autotile::{AutotileMode, AutotileRule, EAST, NORTH, NORTH_EAST, SOUTH, WEST};
+    use rpggame::map::Map;
+    use rpggame::tileset::TilesetManifest;
+
+    fn grass_rule() -> AutotileRule {
+        let manifest = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        manifest.autotile_rule("grass", "grass").unwrap().clone()
+    }
+
+    #[test]
+    fn test_autotile_region_l_shape() {
+        let rule = grass_rule();
+        let region = vec![
+            vec![true, false],
+            vec![true, true],
+        ];
+
+        let tiles = rule.autotile_region(&region);
+
+        assert_eq!(tiles, vec![
+            vec![Some(rule.tile_for_mask(SOUTH)), None],
+            vec![Some(rule.tile_for_mask(NORTH | EAST)), Some(rule.tile_for_mask(WEST))],
+        ]);
+        assert_eq!(tiles[1][0], Some(67));
+    }
+
+    #[test]
+    fn test_autotile_around_painted_hole() {
+        let mut map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        let rule = grass_rule();
+
+        map.block_at_mut(5, 5).unwrap().tile = 0;
+        map.autotile_around(5, 5, "grass", &rule);
+
+        assert_eq!(map.block_at(5, 4).unwrap().tile, 68);
+        assert_eq!(map.block_at(4, 5).unwrap().tile, 58);
+        assert_eq!(map.block_at(6, 5).unwrap().tile, 56);
+        assert_eq!(map.block_at(5, 6).unwrap().tile, 46);
+        assert_eq!(map.block_at(4, 4).unwrap().tile, 57);
+        assert_eq!(map.block_at(5, 5).unwrap().tile, 0);
+    }
+
+    #[test]
+    fn test_blob_mask_ignores_diagonal_without_both_sides() {
+        let rule = AutotileRule { mode: AutotileMode::Blob, default: 0, tiles: HashMap::new(), variants: Vec::new() };
+        let terrain = [(0, -1), (1, -1), (1, 0)];
+        let corner_only = [(1, -1), (1, 0)];
+
+        let full_corner = rule.neighbour_mask(0, 0, |x, y| terrain.contains(&(x, y)));
+        let open_corner = rule.neighbour_mask(0, 0, |x, y| corner_only.contains(&(x, y)));
+
+        assert_eq!(full_corner, NORTH | EAST | NORTH_EAST);
+        assert_eq!(open_corner, EAST);
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use rpggame::map::{Block, Collision, Map, MapError, MapLayer};
+    use rpggame::tileset::TileOverrides;
+
+    fn layered_map() -> Map {
+        let mut map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        let size = map.chunk_size;
+        let mut objects = vec![vec![None; size]; size];
+        objects[3][4] = Some(Block {
+            tile: 12,
+            texture: String::from("grass"),
+            overrides: TileOverrides { collision: Some(Collision::Full), tags: Some(vec![String::from("rock")]), ..Default::default() },
+        });
+        map.chunks[0][0].layers.insert(MapLayer::Objects, objects);
+        let second = map.chunks[0][0].clone();
+        map.chunks[0].push(second);
+        map
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let map = layered_map();
+
+        let bytes = map.to_binary(false).unwrap();
+
+        assert_eq!(Map::from_binary(&bytes).unwrap(), map);
+    }
+
+    #[test]
+    fn test_compressed_binary_round_trip() {
+        let map = layered_map();
+
+        let bytes = map.to_binary(true).unwrap();
+
+        assert_eq!(Map::from_binary(&bytes).unwrap(), map);
+    }
+
+    #[test]
+    fn test_binary_round_trip_keeps_json_identical() {
+        let json = std::fs::read_to_string("assets/maps/main.map.json").unwrap();
+        let map = Map::from_json_str(&json).unwrap();
+
+        let decoded = Map::from_binary(&map.to_binary(true).unwrap()).unwrap();
+
+        assert_eq!(decoded.to_json_string().unwrap(), json);
+    }
+
+    #[test]
+    fn test_binary_is_smaller_than_json() {
+        let map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        let json = map.to_json_string().unwrap();
+
+        let bytes = map.to_binary(false).unwrap();
+
+        assert!(bytes.len() * 10 < json.len());
+    }
+
+    #[test]
+    fn test_binary_rejects_bad_header_and_version() {
+        let mut bytes = layered_map().to_binary(false).unwrap();
+
+        assert!(matches!(Map::from_binary(b"{}"), Err(MapError::Binary(_))));
+        bytes[4] = 99;
+        assert!(matches!(Map::from_binary(&bytes), Err(MapError::Binary(_))));
+    }
+
+    #[test]
+    fn test_binary_rejects_truncated_data() {
+        let bytes = layered_map().to_binary(false).unwrap();
+
+        let result = Map::from_binary(&bytes[..bytes.len() - 3]);
+
+        assert!(matches!(result, Err(MapError::Binary(_))));
+    }
+
+    fn header(body: &[u8]) -> Vec<u8> {
+        let mut bytes = b"RPGM".to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn test_binary_rejects_truncated_header() {
+        for len in 0..8 {
+            let bytes = header(&[2, 1, 1]);
+            assert!(matches!(Map::from_binary(&bytes[..len.min(bytes.len())]), Err(MapError::Binary(_))), "length {len}");
+        }
+    }
+
+    #[test]
+    fn test_binary_rejects_oversized_counts() {
+        // chunk_size 2, then a row count of u64::MAX.
+        let huge_rows = header(&[2, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+        // One row of u64::MAX chunks and an empty palette.
+        let huge_row = header(&[2, 1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 0]);
+        // A palette longer than the data.
+        let huge_palette = header(&[2, 0, 0xff, 0xff, 0xff, 0x7f]);
+        // One chunk of 2^20 x 2^20 blocks written as a single run.
+        let huge_chunk = header(&[0x80, 0x80, 0x40, 1, 1, 0, 1, 0, 0]);
+        // One 2x2 chunk whose second run is u64::MAX blocks long.
+        let huge_run = header(&[2, 1, 1, 0, 1, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 0, 0]);
+
+        for bytes in [huge_rows, huge_row, huge_palette, huge_chunk, huge_run] {
+            assert!(matches!(Map::from_binary(&bytes), Err(MapError::Binary(_))), "{bytes:?}");
+        }
+    }
+}
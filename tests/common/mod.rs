@@ -0,0 +1,28 @@
+// Helpers shared by the editor, history and tools tests.
+#![allow(dead_code)]
+
+use rpggame::map::{Block, Chunk, Map};
+use rpggame::tileset::{TileOverrides, TilesetManifest};
+
+pub fn block(texture: &str, tile: usize) -> Block {
+    Block { tile, texture: texture.to_string(), overrides: TileOverrides::default() }
+}
+
+// chunks_wide x chunks_high chunks of plain dirt.
+pub fn dirt_map(chunk_size: usize, chunks_wide: usize, chunks_high: usize) -> Map {
+    filled_map(block("dirt", 0), chunk_size, chunks_wide, chunks_high)
+}
+
+// Like dirt_map, but with a texture of the shipped tileset manifest so the map validates.
+pub fn grass_map(chunk_size: usize, chunks_wide: usize, chunks_high: usize) -> Map {
+    filled_map(block("grass", 0), chunk_size, chunks_wide, chunks_high)
+}
+
+fn filled_map(block: Block, chunk_size: usize, chunks_wide: usize, chunks_high: usize) -> Map {
+    let chunk = Chunk { blocks: vec![vec![block; chunk_size]; chunk_size], layers: Default::default() };
+    Map { chunk_size, chunks: vec![vec![chunk; chunks_wide]; chunks_high] }
+}
+
+pub fn tilesets() -> TilesetManifest {
+    TilesetManifest::load_from_json("assets/tilesets.json").unwrap()
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use bevy::render::camera::{camera_system, ManualTextureViews};
+    use bevy::window::{PrimaryWindow, WindowCreated, WindowResized, WindowScaleFactorChanged};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rpggame::coords::{block_size, block_to_chunk, block_to_world, chunk_to_block, chunk_to_world, screen_to_world, world_to_block, world_to_chunk};
+    use rpggame::resolution::Resolution;
+
+    const CASES: usize = 1000;
+
+    fn test_resolution() -> Resolution {
+        Resolution {
+            screen_dimensions: Vec2::new(1920.0, 1080.0),
+            pixel_ratio: 4,
+            frame_size: UVec2::new(16, 16),
+            map_translation: Vec2::new(64.0, -64.0),
+        }
+    }
+
+    fn random_block(rng: &mut StdRng) -> IVec2 {
+        IVec2::new(rng.gen_range(-500..500), rng.gen_range(-500..500))
+    }
+
+    #[test]
+    fn test_block_round_trips_through_world() {
+        let resolution = test_resolution();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..CASES {
+            let block = random_block(&mut rng);
+            assert_eq!(world_to_block(block_to_world(block, &resolution), &resolution), block);
+        }
+    }
+
+    #[test]
+    fn test_every_point_of_a_block_maps_back_to_it() {
+        let resolution = test_resolution();
+        let half = block_size(&resolution) / 2.0;
+        let mut rng = StdRng::seed_from_u64(2);
+
+        for _ in 0..CASES {
+            let block = random_block(&mut rng);
+            let offset = Vec2::new(rng.gen_range(-half.x..half.x), rng.gen_range(-half.y + 0.01..half.y));
+            assert_eq!(world_to_block(block_to_world(block, &resolution) + offset, &resolution), block, "offset {offset}");
+        }
+    }
+
+    #[test]
+    fn test_world_to_block_near_the_origin() {
+        let resolution = test_resolution();
+
+        assert_eq!(block_to_world(IVec2::new(1, 1), &resolution), Vec2::new(64.0, -64.0));
+        assert_eq!(world_to_block(Vec2::new(31.9, -31.9), &resolution), IVec2::ZERO);
+        assert_eq!(world_to_block(Vec2::new(32.0, -32.1), &resolution), IVec2::new(1, 1));
+        assert_eq!(world_to_block(Vec2::new(-32.1, 32.1), &resolution), IVec2::new(-1, -1));
+        assert_eq!(world_to_block(Vec2::new(-96.1, 96.1), &resolution), IVec2::new(-2, -2));
+    }
+
+    #[test]
+    fn test_block_round_trips_through_chunk() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for _ in 0..CASES {
+            let block = random_block(&mut rng);
+            let chunk_size = rng.gen_range(1..40);
+            let (chunk, local) = block_to_chunk(block, chunk_size);
+
+            assert!(local.max_element() < chunk_size as u32);
+            assert_eq!(chunk_to_block(chunk, local, chunk_size), block);
+        }
+    }
+
+    #[test]
+    fn test_chunk_boundaries() {
+        assert_eq!(block_to_chunk(IVec2::new(15, 0), 16), (IVec2::ZERO, UVec2::new(15, 0)));
+        assert_eq!(block_to_chunk(IVec2::new(16, 31), 16), (IVec2::new(1, 1), UVec2::new(0, 15)));
+        assert_eq!(block_to_chunk(IVec2::new(-1, -16), 16), (IVec2::new(-1, -1), UVec2::new(15, 0)));
+        assert_eq!(block_to_chunk(IVec2::new(-17, 0), 16), (IVec2::new(-2, 0), UVec2::new(15, 0)));
+    }
+
+    #[test]
+    fn test_world_to_chunk_matches_the_chunk_of_the_block() {
+        let resolution = test_resolution();
+        let mut rng = StdRng::seed_from_u64(4);
+
+        for _ in 0..CASES {
+            let position = Vec2::new(rng.gen_range(-20000.0..20000.0), rng.gen_range(-20000.0..20000.0));
+            let chunk_size = rng.gen_range(1..40);
+            let block = world_to_block(position, &resolution);
+
+            assert_eq!(world_to_chunk(position, chunk_size, &resolution), block_to_chunk(block, chunk_size).0);
+        }
+        // The first block of a chunk is centred on the chunk translation.
+        assert_eq!(world_to_chunk(chunk_to_world(IVec2::new(-3, 2), 8, &resolution), 8, &resolution), IVec2::new(-3, 2));
+    }
+
+    // A 2D camera at camera_position with its projection computed for a 1280x720 window.
+    fn camera_at(camera_position: Vec2, scale: f32) -> (Camera, GlobalTransform) {
+        let mut world = World::new();
+        world.init_resource::<Events<WindowCreated>>();
+        world.init_resource::<Events<WindowResized>>();
+        world.init_resource::<Events<WindowScaleFactorChanged>>();
+        world.init_resource::<Events<AssetEvent<Image>>>();
+        world.init_resource::<Assets<Image>>();
+        world.init_resource::<ManualTextureViews>();
+        let window = world.spawn((Window { resolution: (1280.0, 720.0).into(), ..default() }, PrimaryWindow)).id();
+        world.send_event(WindowCreated { window });
+        let transform = GlobalTransform::from_translation(camera_position.extend(0.0));
+        let camera = world.spawn((Camera2d, OrthographicProjection { scale, ..OrthographicProjection::default_2d() }, transform)).id();
+
+        world.run_system_once(camera_system::<OrthographicProjection>).unwrap();
+        (world.get::<Camera>(camera).unwrap().clone(), transform)
+    }
+
+    fn assert_near(actual: Option<Vec2>, expected: Vec2) {
+        assert!(actual.is_some_and(|actual| actual.abs_diff_eq(expected, 1e-3)), "{actual:?} != {expected}");
+    }
+
+    #[test]
+    fn test_screen_to_world() {
+        let camera_position = Vec2::new(100.0, -50.0);
+        let (camera, transform) = camera_at(camera_position, 1.0);
+
+        assert_near(screen_to_world(&camera, &transform, Vec2::new(640.0, 360.0)), camera_position);
+        assert_near(screen_to_world(&camera, &transform, Vec2::ZERO), camera_position + Vec2::new(-640.0, 360.0));
+        assert_near(screen_to_world(&camera, &transform, Vec2::new(1280.0, 720.0)), camera_position + Vec2::new(640.0, -360.0));
+    }
+
+    #[test]
+    fn test_screen_to_world_follows_the_zoom() {
+        let (camera, transform) = camera_at(Vec2::ZERO, 2.0);
+
+        assert_near(screen_to_world(&camera, &transform, Vec2::ZERO), Vec2::new(-1280.0, 720.0));
+    }
+
+    #[test]
+    fn test_screen_to_world_without_a_viewport() {
+        assert_eq!(screen_to_world(&Camera::default(), &GlobalTransform::IDENTITY, Vec2::ZERO), None);
+    }
+}
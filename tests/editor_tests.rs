@@ -0,0 +1,133 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use rpggame::autotile::{EAST, WEST};
+    use rpggame::editor::{paint_block, paint_hovered_block, set_collision, set_overrides, track_brush_strokes, use_selection, EditorState};
+    use rpggame::gridselector::{BlockSelection, HoveredBlock};
+    use rpggame::history::{changed_chunks, EditHistory};
+    use rpggame::map::{Block, ChunkChanged, Collision, LoadedMap, Map, MapLayer};
+    use rpggame::tileset::{TileOverrides, TilesetRegistry};
+    use rpggame::tools::Stamp;
+    use crate::common::{block, tilesets};
+
+    fn dirt_map() -> Map {
+        crate::common::dirt_map(2, 2, 2)
+    }
+
+    // The brush and paste systems in the order the editor runs them.
+    fn editor_app(state: EditorState) -> App {
+        let mut app = App::new();
+        app
+            .add_event::<ChunkChanged>()
+            .insert_resource(ButtonInput::<MouseButton>::default())
+            .insert_resource(HoveredBlock(1, 1))
+            .insert_resource(BlockSelection::default())
+            .insert_resource(TilesetRegistry::from_manifest(tilesets()))
+            .insert_resource(LoadedMap { map: dirt_map() })
+            .insert_resource(EditHistory::default())
+            .insert_resource(state)
+            .add_systems(Update, (track_brush_strokes, paint_hovered_block, use_selection).chain());
+        app
+    }
+
+    // Runs one frame; buttons stay held until released.
+    fn frame(app: &mut App, press: Option<MouseButton>, release: Option<MouseButton>) {
+        let mut mouse_input = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+        mouse_input.clear();
+        press.into_iter().for_each(|button| mouse_input.press(button));
+        release.into_iter().for_each(|button| mouse_input.release(button));
+        app.update();
+    }
+
+    #[test]
+    fn test_paint_and_erase_overlay_blocks() {
+        let mut map = dirt_map();
+
+        let changes = paint_block(&mut map, 3, 0, MapLayer::Objects, Some(block("trees", 1)), &tilesets());
+        assert_eq!(changed_chunks(&map, &changes), [IVec2::new(1, 0)]);
+        assert_eq!((changes[0].before.as_ref(), changes[0].after.as_ref()), (None, Some(&block("trees", 1))));
+        assert_eq!(map.layer_block_at(3, 0, MapLayer::Objects), Some(&block("trees", 1)));
+
+        paint_block(&mut map, 3, 0, MapLayer::Objects, None, &tilesets());
+        assert_eq!(map, dirt_map());
+        assert!(paint_block(&mut map, 3, 0, MapLayer::Objects, None, &tilesets()).is_empty());
+    }
+
+    #[test]
+    fn test_base_blocks_are_replaced_but_never_erased() {
+        let mut map = dirt_map();
+
+        assert!(paint_block(&mut map, 0, 0, MapLayer::Base, None, &tilesets()).is_empty());
+        assert!(paint_block(&mut map, 9, 9, MapLayer::Base, Some(block("dirt", 3)), &tilesets()).is_empty());
+        assert_eq!(paint_block(&mut map, 0, 0, MapLayer::Base, Some(block("dirt", 3)), &tilesets()).len(), 1);
+        assert_eq!(map.block_at(0, 0).unwrap().tile, 3);
+    }
+
+    #[test]
+    fn test_painting_terrain_autotiles_neighbours_across_chunks() {
+        let mut map = dirt_map();
+
+        paint_block(&mut map, 1, 1, MapLayer::Base, Some(block("grass", 57)), &tilesets());
+        let changes = paint_block(&mut map, 2, 1, MapLayer::Base, Some(block("grass", 57)), &tilesets());
+
+        assert_eq!(changed_chunks(&map, &changes), [IVec2::new(1, 0)]);
+        // A grass pair gets an east edge on the left block and a west edge on the right one.
+        let tiles = (map.block_at(1, 1).unwrap().tile, map.block_at(2, 1).unwrap().tile);
+        let rule = tilesets().autotile_rule("grass", "grass").unwrap().clone();
+        assert_eq!(tiles, (rule.tile_for_mask(EAST), rule.tile_for_mask(WEST)));
+        assert!(paint_block(&mut map, 2, 1, MapLayer::Base, Some(block("grass", 57)), &tilesets()).is_empty());
+    }
+
+    #[test]
+    fn test_set_overrides_keeps_the_tile() {
+        let mut map = dirt_map();
+        let overrides = TileOverrides { collision: Some(Collision::Full), ..Default::default() };
+
+        let changes = set_overrides(&mut map, 1, 0, MapLayer::Base, overrides.clone());
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(map.block_at(1, 0).unwrap(), &Block { overrides: overrides.clone(), ..block("dirt", 0) });
+        assert!(set_overrides(&mut map, 1, 0, MapLayer::Base, overrides).is_empty());
+        assert!(set_overrides(&mut map, 1, 0, MapLayer::Objects, TileOverrides::default()).is_empty());
+    }
+
+    #[test]
+    fn test_set_collision_keeps_other_overrides() {
+        let mut map = dirt_map();
+        let overrides = TileOverrides { movement_cost: Some(2.0), ..Default::default() };
+        set_overrides(&mut map, 0, 1, MapLayer::Base, overrides.clone());
+
+        let changes = set_collision(&mut map, 0, 1, MapLayer::Base, Some(Collision::Slow));
+        assert_eq!(changed_chunks(&map, &changes), [IVec2::new(0, 0)]);
+        assert_eq!(map.block_at(0, 1).unwrap().overrides, TileOverrides { collision: Some(Collision::Slow), ..overrides.clone() });
+
+        set_collision(&mut map, 0, 1, MapLayer::Base, None);
+        assert_eq!(map.block_at(0, 1).unwrap().overrides, overrides);
+        assert!(set_collision(&mut map, 0, 1, MapLayer::Objects, Some(Collision::Full)).is_empty());
+    }
+
+    #[test]
+    fn test_the_paste_click_does_not_paint_while_held() {
+        let stamp = Stamp { blocks: vec![vec![Some(block("dirt", 3))]] };
+        let state = EditorState { enabled: true, brush: Some(block("dirt", 5)), stamp: Some(stamp), pasting: true, ..Default::default() };
+        let mut app = editor_app(state);
+
+        frame(&mut app, Some(MouseButton::Left), None);
+        let pasted = app.world().resource::<LoadedMap>().map.clone();
+        assert_eq!(pasted.block_at(1, 1).unwrap().tile, 3);
+
+        // The button is still held, first in place and then dragged onto the next block.
+        frame(&mut app, None, None);
+        app.insert_resource(HoveredBlock(2, 1));
+        frame(&mut app, None, None);
+        assert_eq!(app.world().resource::<LoadedMap>().map, pasted);
+        assert_eq!(app.world().resource::<EditHistory>().undo_len(), 1);
+
+        // Once released, the next click paints the brush again.
+        frame(&mut app, None, Some(MouseButton::Left));
+        frame(&mut app, Some(MouseButton::Left), None);
+        assert_eq!(app.world().resource::<LoadedMap>().map.block_at(2, 1).unwrap().tile, 5);
+    }
+}
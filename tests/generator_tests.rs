@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use rpggame::generator::{generate, Biome, GeneratorError, GeneratorParams};
+    use rpggame::map::Map;
+    use rpggame::tileset::TilesetManifest;
+
+    fn test_tilesets() -> TilesetManifest {
+        let mut manifest = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let grass = &mut manifest.tilesets[0];
+        let mut meadow = grass.autotiles["grass"].clone();
+        meadow.variants = vec![1, 2, 3];
+        grass.autotiles.insert(String::from("meadow"), meadow);
+        manifest
+    }
+
+    fn test_params(seed: u64) -> GeneratorParams {
+        GeneratorParams {
+            seed,
+            size_in_chunks: [2, 2],
+            chunk_size: 4,
+            biomes: vec![
+                Biome { texture: String::from("grass"), terrain: String::from("grass"), weight: 1.0 },
+                Biome { texture: String::from("grass"), terrain: String::from("meadow"), weight: 2.0 },
+            ],
+            noise_scale: 3.0,
+            decoration_chance: 0.3,
+        }
+    }
+
+    #[test]
+    fn test_generate_same_seed_is_byte_identical() {
+        let first = generate(&test_params(7), &test_tilesets()).unwrap().to_json_string().unwrap();
+        let second = generate(&test_params(7), &test_tilesets()).unwrap().to_json_string().unwrap();
+
+        assert_eq!(first.as_bytes(), second.as_bytes());
+    }
+
+    #[test]
+    fn test_generate_matches_fixture() {
+        let generated = generate(&test_params(7), &test_tilesets()).unwrap().to_json_string().unwrap();
+
+        let fixture = std::fs::read_to_string("tests/fixtures/generated_seed_7.map.json").unwrap();
+        assert_eq!(generated.as_bytes(), fixture.as_bytes());
+    }
+
+    #[test]
+    fn test_generate_different_seeds_differ() {
+        let first = generate(&test_params(7), &test_tilesets()).unwrap().to_json_string().unwrap();
+        let second = generate(&test_params(8), &test_tilesets()).unwrap().to_json_string().unwrap();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_generate_builds_every_chunk() {
+        let mut params = test_params(7);
+        params.size_in_chunks = [3, 2];
+
+        let map = generate(&params, &test_tilesets()).unwrap();
+
+        assert_eq!(map.chunks.len(), 2);
+        assert!(map.chunks.iter().all(|row| row.len() == 3));
+        assert!(Map::from_json_str(&map.to_json_string().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_generate_single_biome_matches_main_map() {
+        let main = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        let params = GeneratorParams {
+            seed: 1,
+            size_in_chunks: [1, 1],
+            chunk_size: 16,
+            biomes: vec![Biome { texture: String::from("grass"), terrain: String::from("grass"), weight: 1.0 }],
+            noise_scale: 8.0,
+            decoration_chance: 0.0,
+        };
+
+        let map = generate(&params, &test_tilesets()).unwrap();
+
+        assert_eq!(map.to_json_string().unwrap(), main.to_json_string().unwrap());
+    }
+
+    #[test]
+    fn test_generate_decorates_grass_with_the_atlas_variants() {
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let variants = tilesets.tilesets[0].autotiles["grass"].variants.clone();
+        let params = GeneratorParams {
+            seed: 3,
+            size_in_chunks: [1, 1],
+            chunk_size: 16,
+            biomes: vec![Biome { texture: String::from("grass"), terrain: String::from("grass"), weight: 1.0 }],
+            noise_scale: 8.0,
+            decoration_chance: 0.5,
+        };
+
+        let map = generate(&params, &tilesets).unwrap();
+
+        assert!(!variants.is_empty());
+        let decorated = map.chunks[0][0].blocks.iter().flatten().filter(|block| variants.contains(&block.tile)).count();
+        assert!(decorated > 0);
+    }
+
+    #[test]
+    fn test_generate_unknown_terrain() {
+        let mut params = test_params(7);
+        params.biomes[0].terrain = String::from("lava");
+
+        let result = generate(&params, &test_tilesets());
+
+        assert!(matches!(result, Err(GeneratorError::UnknownAutotile { .. })));
+    }
+}
@@ -0,0 +1,90 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use rpggame::editor::{paint_block, set_overrides};
+    use rpggame::history::{EditCommand, EditHistory, EditKind};
+    use rpggame::map::{Block, Collision, Map, MapLayer};
+    use rpggame::tileset::TileOverrides;
+    use crate::common::{block, tilesets};
+
+    fn dirt_map() -> Map {
+        crate::common::dirt_map(4, 2, 1)
+    }
+
+    fn paint(history: &mut EditHistory, map: &mut Map, x: isize, y: isize, layer: MapLayer, block: Option<Block>) {
+        let kind = if block.is_some() { EditKind::Paint } else { EditKind::Erase };
+        let changes = paint_block(map, x, y, layer, block, &tilesets());
+        history.record(EditCommand { kind, changes });
+    }
+
+    #[test]
+    fn test_undo_and_redo_a_sequence_of_edits() {
+        let mut map = dirt_map();
+        let mut history = EditHistory::default();
+        let mut snapshots = vec![map.clone()];
+
+        paint(&mut history, &mut map, 4, 1, MapLayer::Base, Some(block("grass", 57)));
+        snapshots.push(map.clone());
+        paint(&mut history, &mut map, 5, 1, MapLayer::Base, Some(block("grass", 57)));
+        snapshots.push(map.clone());
+        paint(&mut history, &mut map, 4, 2, MapLayer::Base, Some(block("grass", 57)));
+        snapshots.push(map.clone());
+        paint(&mut history, &mut map, 4, 2, MapLayer::Objects, Some(block("trees", 1)));
+        snapshots.push(map.clone());
+        paint(&mut history, &mut map, 4, 2, MapLayer::Objects, None);
+        snapshots.push(map.clone());
+        let changes = set_overrides(&mut map, 0, 3, MapLayer::Base, TileOverrides { collision: Some(Collision::Slow), ..Default::default() });
+        history.record(EditCommand { kind: EditKind::Property, changes });
+        snapshots.push(map.clone());
+
+        for expected in snapshots.iter().rev().skip(1) {
+            assert!(history.undo(&mut map).is_some());
+            assert_eq!(&map, expected);
+        }
+        assert!(history.undo(&mut map).is_none());
+
+        for expected in snapshots.iter().skip(1) {
+            assert!(history.redo(&mut map).is_some());
+            assert_eq!(&map, expected);
+        }
+        assert!(history.redo(&mut map).is_none());
+    }
+
+    #[test]
+    fn test_brush_strokes_undo_as_one_step() {
+        let mut map = dirt_map();
+        let mut history = EditHistory::default();
+
+        history.begin_stroke(EditKind::Paint);
+        for x in 0..6 {
+            history.extend_stroke(paint_block(&mut map, x, 0, MapLayer::Details, Some(block("flowers", 2)), &tilesets()));
+        }
+        history.end_stroke();
+        history.begin_stroke(EditKind::Erase);
+        history.end_stroke();
+
+        assert_eq!(history.undo_len(), 1);
+        let chunks = history.undo(&mut map).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(map, dirt_map());
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_new_edits_clear_redo() {
+        let mut map = dirt_map();
+        let mut history = EditHistory::new(3);
+
+        for tile in 1..=5 {
+            paint(&mut history, &mut map, 0, 0, MapLayer::Base, Some(block("dirt", tile)));
+        }
+        assert_eq!(history.undo_len(), 3);
+        while history.undo(&mut map).is_some() {}
+        assert_eq!(map.block_at(0, 0).unwrap().tile, 2);
+
+        history.redo(&mut map);
+        paint(&mut history, &mut map, 1, 0, MapLayer::Base, Some(block("dirt", 9)));
+        assert_eq!(history.redo_len(), 0);
+        assert_eq!(history.undo_len(), 2);
+    }
+}
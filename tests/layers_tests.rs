@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use bevy::render::view::RenderLayers;
+    use rpggame::layers::{LayerVisibility, CURSOR_LAYER, EDITOR_LAYER, MAP_LAYER, PLAYER_LAYER};
+
+    #[test]
+    fn test_layers_are_distinct() {
+        let mut layers = vec![CURSOR_LAYER, PLAYER_LAYER, MAP_LAYER, EDITOR_LAYER];
+        layers.sort();
+        layers.dedup();
+        assert_eq!(layers.len(), 4);
+    }
+
+    #[test]
+    fn test_editor_layer_is_hidden_by_default() {
+        let visibility = LayerVisibility::default();
+
+        assert_eq!(visibility.render_layers(), RenderLayers::from_layers(&[MAP_LAYER, PLAYER_LAYER, CURSOR_LAYER]));
+    }
+
+    #[test]
+    fn test_hidden_layers_are_left_out() {
+        let visibility = LayerVisibility { map: false, player: true, editor: true, cursor: false };
+
+        let render_layers = visibility.render_layers();
+        assert!(render_layers.intersects(&RenderLayers::layer(EDITOR_LAYER)));
+        assert!(!render_layers.intersects(&RenderLayers::layer(MAP_LAYER)));
+        assert!(!render_layers.intersects(&RenderLayers::layer(CURSOR_LAYER)));
+    }
+}
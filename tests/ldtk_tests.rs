@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use rpggame::ldtk::{import_ldtk_file, import_ldtk_json, LdtkImportOptions};
+    use rpggame::map::{Block, Collision, MapError, MapLayer};
+    use rpggame::tileset::TileOverrides;
+
+    const SMALL_LDTK: &str = "tests/fixtures/ldtk/small.ldtk";
+
+    fn options(level: Option<&str>) -> LdtkImportOptions {
+        LdtkImportOptions {
+            chunk_size: 2,
+            level: level.map(str::to_string),
+            fill: Block { tile: 57, texture: "grass".to_string(), overrides: TileOverrides::default() },
+        }
+    }
+
+    #[test]
+    fn test_import_maps_tile_layers_onto_blocks() {
+        let map = import_ldtk_file(SMALL_LDTK, &options(None)).unwrap().map;
+
+        assert_eq!((map.chunks.len(), map.chunks[0].len()), (1, 2));
+        let base = map.block_at(1, 0).unwrap();
+        assert_eq!((base.texture.as_str(), base.tile), ("grass", 46));
+        assert_eq!(map.block_at(1, 1).unwrap().tile, 57);
+
+        let objects = map.chunks[0][0].layer_blocks(MapLayer::Objects);
+        assert_eq!(objects.len(), 1);
+        assert_eq!((objects[0].2.texture.as_str(), objects[0].2.tile), ("trees", 1));
+    }
+
+    #[test]
+    fn test_import_maps_int_grid_onto_collision() {
+        let map = import_ldtk_file(SMALL_LDTK, &options(None)).unwrap().map;
+
+        assert_eq!(map.block_at(0, 0).unwrap().overrides.collision, Some(Collision::Full));
+        assert_eq!(map.block_at(1, 1).unwrap().overrides.collision, Some(Collision::Slow));
+        assert_eq!(map.block_at(2, 1).unwrap().overrides.collision, None);
+        assert_eq!(map.block_at(1, 0).unwrap().overrides.collision, None);
+    }
+
+    #[test]
+    fn test_import_collects_entity_spawns() {
+        let entities = import_ldtk_file(SMALL_LDTK, &options(None)).unwrap().entities;
+
+        assert_eq!(entities.len(), 2);
+        assert_eq!((entities[0].identifier.as_str(), entities[0].block), ("Player", [1, 0]));
+        assert_eq!(entities[0].fields["facing"], "south");
+        assert_eq!(entities[1].layer, "Entities");
+        assert_eq!(entities[1].fields["contents"], serde_json::json!(["potion"]));
+    }
+
+    #[test]
+    fn test_import_reports_what_it_cannot_represent() {
+        let import = import_ldtk_file(SMALL_LDTK, &options(Some("Meadow"))).unwrap();
+        let warnings = import.warnings.join("\n");
+
+        assert!(warnings.contains("IntGrid value 3"), "{}", warnings);
+        assert!(warnings.contains("1 flipped"), "{}", warnings);
+        assert!(warnings.contains("1 empty Base cells"), "{}", warnings);
+
+        assert!(matches!(import_ldtk_file(SMALL_LDTK, &options(Some("Cave"))), Err(MapError::Import(_))));
+        let external = r#"{"externalLevels": true, "defs": {}, "levels": []}"#;
+        assert!(matches!(import_ldtk_json(external, &options(None)), Err(MapError::Import(_))));
+    }
+
+    fn single_layer_project(layer: &str) -> String {
+        format!(r#"{{"defs": {{"layers": [], "tilesets": [{{"uid": 1, "identifier": "Grass"}}]}},
+            "levels": [{{"identifier": "Level", "layerInstances": [{}]}}]}}"#, layer)
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_grid_dimensions() {
+        let zero_grid = single_layer_project(r#"{"__identifier": "Base", "__type": "Tiles", "__cWid": 2, "__cHei": 1, "__gridSize": 0,
+            "__tilesetDefUid": 1, "layerDefUid": 1, "gridTiles": [{"px": [16, 0], "t": 3}]}"#);
+        assert!(matches!(import_ldtk_json(&zero_grid, &options(None)), Err(MapError::Import(_))));
+
+        let zero_width = single_layer_project(r#"{"__identifier": "Collision", "__type": "IntGrid", "__cWid": 0, "__cHei": 1, "__gridSize": 16,
+            "layerDefUid": 2, "intGridCsv": [1, 0]}"#);
+        assert!(matches!(import_ldtk_json(&zero_width, &options(None)), Err(MapError::Import(_))));
+    }
+
+    #[test]
+    fn test_import_skips_tiles_at_negative_positions() {
+        let project = single_layer_project(r#"{"__identifier": "Base", "__type": "Tiles", "__cWid": 2, "__cHei": 1, "__gridSize": 16,
+            "__tilesetDefUid": 1, "layerDefUid": 1, "gridTiles": [{"px": [-16, 0], "t": 3}, {"px": [16, 0], "t": 4}]}"#);
+
+        let map = import_ldtk_json(&project, &options(None)).unwrap().map;
+
+        assert_eq!(map.block_at(0, 0).unwrap().tile, 57);
+        assert_eq!(map.block_at(1, 0).unwrap().tile, 4);
+    }
+}
@@ -0,0 +1,267 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use bevy::prelude::*;
+    use rpggame::coords;
+    use rpggame::game::MainCamera;
+    use rpggame::map::{apply_loaded_map, stream_chunks, BlockLocation, ChunkStreaming, LoadedChunks, LoadedMap, Map, MapError, MapHandle, MapLayer, MapReplaced};
+    use rpggame::player::PLAYER_Z;
+    use rpggame::resolution::Resolution;
+    use rpggame::map::Collision;
+    use rpggame::map_stats::MapStats;
+    use rpggame::tileset::{TileOverrides, TilesetManifest, TilesetRegistry};
+    use crate::common::{block, grass_map, tilesets};
+
+    const SMALL_MAP: &str = r#"{
+        "chunk_size": 2,
+        "chunks": [[{ "blocks": [
+            [{ "tile": 1, "texture": "grass" }, { "tile": 2, "texture": "grass" }],
+            [{ "tile": 3, "collision": "Full", "texture": "grass" }, { "tile": 80, "texture": "stone" }]
+        ]}]]
+    }"#;
+
+    #[test]
+    fn test_main_map_loads_as_base_only() {
+        let map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+
+        let chunk = &map.chunks[0][0];
+        assert!(chunk.layers.is_empty());
+        assert_eq!(chunk.layer_blocks(MapLayer::Base).len(), map.chunk_size * map.chunk_size);
+        assert!(chunk.layer_blocks(MapLayer::Objects).is_empty());
+    }
+
+    #[test]
+    fn test_main_map_matches_tileset_manifest() {
+        let map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        let manifest = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+
+        assert_eq!(manifest.tile_counts().get("grass"), Some(&77));
+        assert!(map.validate_tiles(&manifest.tile_counts()).is_ok());
+    }
+
+    #[test]
+    fn test_overlay_layers_draw_above_the_player() {
+        for layer in [MapLayer::Base, MapLayer::Objects, MapLayer::Details] {
+            assert!(layer.z() < PLAYER_Z, "{:?} should be under the player", layer);
+        }
+        assert!(PLAYER_Z < MapLayer::Particles.z());
+        assert!(MapLayer::Particles.z() < MapLayer::Weather.z());
+    }
+
+    #[test]
+    fn test_properties_at_applies_overrides() {
+        let map = Map::from_json_str(SMALL_MAP).unwrap();
+        let manifest = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+
+        let defaults = map.properties_at(0, 0, &manifest).unwrap();
+        let overridden = map.properties_at(0, 1, &manifest).unwrap();
+
+        assert_eq!(defaults.collision, Collision::Passable);
+        assert_eq!(overridden.collision, Collision::Full);
+        assert_eq!(map.chunks[0][0].blocks[0][0].overrides, TileOverrides::default());
+        assert!(map.properties_at(2, 0, &manifest).is_none());
+    }
+
+    #[test]
+    fn test_properties_at_uses_tileset_defaults() {
+        let map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        let manifest = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+
+        let properties = map.properties_at(1, 1, &manifest).unwrap();
+
+        assert_eq!(properties.footstep_sound.as_deref(), Some("grass"));
+        assert!(properties.has_tag("grass"));
+    }
+
+    #[test]
+    fn test_map_stats_counts_tiles_and_collision() {
+        let map = Map::from_json_str(SMALL_MAP).unwrap();
+        let manifest = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+
+        let stats = MapStats::collect(&map, &manifest);
+
+        assert_eq!(stats.chunk_count, 1);
+        assert_eq!(stats.block_count, 4);
+        assert_eq!(stats.tile_histogram.get(&("stone".to_string(), 80)), Some(&1));
+        assert_eq!(stats.collision_coverage(Collision::Full), 0.25);
+        assert_eq!(stats.collision_coverage(Collision::Passable), 0.75);
+    }
+
+    #[test]
+    fn test_missing_map_file_is_io_error() {
+        let result = Map::load_from_json("assets/maps/does_not_exist.map.json");
+
+        assert!(matches!(result, Err(MapError::Io(_))));
+    }
+
+    #[test]
+    fn test_invalid_json_is_parse_error() {
+        let result = Map::from_json_str("{ \"chunk_size\": 2, ");
+
+        assert!(matches!(result, Err(MapError::Parse(_))));
+    }
+
+    #[test]
+    fn test_ragged_chunk_is_rejected() {
+        let json = r#"{ "chunk_size": 2, "chunks": [[{ "blocks": [
+            [{ "tile": 1, "collision": "Passable", "texture": "grass" }, { "tile": 1, "collision": "Passable", "texture": "grass" }],
+            [{ "tile": 1, "collision": "Passable", "texture": "grass" }]
+        ]}]] }"#;
+
+        let result = Map::from_json_str(json);
+
+        assert!(matches!(result, Err(MapError::RaggedChunk { layer: MapLayer::Base, row: Some(1), expected: 2, .. })));
+    }
+
+    #[test]
+    fn test_unknown_texture_reports_block() {
+        let map = Map::from_json_str(SMALL_MAP).unwrap();
+        let tile_counts = HashMap::from([("grass".to_string(), 77)]);
+
+        let result = map.validate_tiles(&tile_counts);
+
+        let expected = BlockLocation { chunk: UVec2::new(0, 0), layer: MapLayer::Base, block: UVec2::new(1, 1) };
+        assert!(matches!(result, Err(MapError::UnknownTexture { location, .. }) if location == expected));
+    }
+
+    #[test]
+    fn test_tile_out_of_range_reports_block() {
+        let map = Map::from_json_str(SMALL_MAP).unwrap();
+        let tile_counts = HashMap::from([("grass".to_string(), 77), ("stone".to_string(), 77)]);
+
+        let result = map.validate_tiles(&tile_counts);
+
+        assert!(matches!(result, Err(MapError::TileOutOfRange { tile: 80, tile_count: 77, .. })));
+    }
+
+    #[test]
+    fn test_non_positive_movement_cost_override_is_rejected() {
+        let mut map = Map::from_json_str(SMALL_MAP).unwrap();
+        map.chunks[0][0].blocks[0][1].overrides.movement_cost = Some(0.0);
+        let tile_counts = HashMap::from([("grass".to_string(), 77), ("stone".to_string(), 81)]);
+
+        let result = map.validate_tiles(&tile_counts);
+
+        let expected = BlockLocation { chunk: UVec2::new(0, 0), layer: MapLayer::Base, block: UVec2::new(1, 0) };
+        assert!(matches!(result, Err(MapError::InvalidMovementCost { location: Some(location), .. }) if location == expected));
+    }
+
+    #[test]
+    fn test_non_positive_movement_cost_in_manifest_is_rejected() {
+        let manifest = r#"{ "tilesets": [{
+            "name": "grass", "image": "grass.png", "tile_size": [16, 16], "columns": 2, "rows": 2,
+            "tiles": { "3": { "movement_cost": -1.0 } }
+        }] }"#;
+
+        let result = TilesetManifest::from_json_slice(manifest.as_bytes());
+
+        assert!(matches!(result, Err(MapError::InvalidMovementCost { tile: 3, location: None, .. })));
+    }
+
+    // apply_loaded_map with the map asset in place of the asset server.
+    fn loaded_map_app(map: Map) -> App {
+        let mut app = App::new();
+        app
+            .add_event::<AssetEvent<Map>>()
+            .add_event::<MapReplaced>()
+            .init_resource::<Assets<Map>>()
+            .init_resource::<LoadedChunks>()
+            .insert_resource(TilesetRegistry::from_manifest(tilesets()))
+            .add_systems(Update, apply_loaded_map);
+        let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+        app.world_mut().send_event(AssetEvent::LoadedWithDependencies { id: handle.id() });
+        app.insert_resource(MapHandle(handle));
+        app.update();
+        app
+    }
+
+    fn replaced_count(app: &mut App) -> usize {
+        app.world_mut().resource_mut::<Events<MapReplaced>>().drain().count()
+    }
+
+    #[test]
+    fn test_tileset_reload_keeps_unsaved_edits() {
+        let mut app = loaded_map_app(grass_map(2, 1, 1));
+        assert_eq!(replaced_count(&mut app), 1);
+        let chunk = app.world_mut().spawn_empty().id();
+        app.world_mut().resource_mut::<LoadedChunks>().0.insert(IVec2::ZERO, chunk);
+
+        app.world_mut().resource_mut::<LoadedMap>().map.set_block(1, 1, MapLayer::Base, Some(block("grass", 4)));
+        let edited = app.world().resource::<LoadedMap>().map.clone();
+        app.insert_resource(TilesetRegistry::from_manifest(tilesets()));
+        app.update();
+
+        assert_eq!(app.world().resource::<LoadedMap>().map, edited);
+        assert_eq!(replaced_count(&mut app), 0);
+        // The chunks are respawned with the new tilesets.
+        assert!(app.world().resource::<LoadedChunks>().0.is_empty());
+        assert!(app.world().get_entity(chunk).is_err());
+    }
+
+    #[test]
+    fn test_map_file_reload_replaces_the_map() {
+        let mut app = loaded_map_app(grass_map(2, 1, 1));
+        replaced_count(&mut app);
+
+        let handle = app.world().resource::<MapHandle>().0.clone();
+        app.world_mut().resource_mut::<Assets<Map>>().insert(&handle, grass_map(2, 2, 1));
+        app.world_mut().send_event(AssetEvent::Modified { id: handle.id() });
+        app.update();
+
+        assert_eq!(app.world().resource::<LoadedMap>().map, grass_map(2, 2, 1));
+        assert_eq!(replaced_count(&mut app), 1);
+    }
+
+    // stream_chunks around a main camera, with the map already loaded.
+    fn streaming_app(map: Map, radius: i32) -> App {
+        let mut app = App::new();
+        app
+            .insert_resource(ChunkStreaming { radius })
+            .insert_resource(LoadedMap { map })
+            .insert_resource(TilesetRegistry::from_manifest(tilesets()))
+            .insert_resource(Resolution {
+                screen_dimensions: Vec2::new(1920.0, 1080.0),
+                pixel_ratio: 4,
+                frame_size: UVec2::new(16, 16),
+                map_translation: Vec2::new(64.0, -64.0),
+            })
+            .init_resource::<LoadedChunks>()
+            .add_systems(Update, stream_chunks);
+        app.world_mut().spawn((MainCamera, GlobalTransform::default()));
+        app
+    }
+
+    fn move_camera_to_chunk(app: &mut App, chunk: IVec2) {
+        let position = coords::chunk_to_world(chunk, app.world().resource::<LoadedMap>().map.chunk_size, app.world().resource::<Resolution>());
+        let mut cameras = app.world_mut().query_filtered::<&mut GlobalTransform, With<MainCamera>>();
+        *cameras.single_mut(app.world_mut()) = GlobalTransform::from_translation(position.extend(0.0));
+        app.update();
+    }
+
+    fn loaded_chunk_coords(app: &App) -> Vec<IVec2> {
+        let mut coords: Vec<IVec2> = app.world().resource::<LoadedChunks>().0.keys().copied().collect();
+        coords.sort_by_key(|coords| (coords.y, coords.x));
+        coords
+    }
+
+    #[test]
+    fn test_chunks_stream_in_and_out_with_the_camera() {
+        let mut app = streaming_app(grass_map(2, 6, 1), 1);
+
+        move_camera_to_chunk(&mut app, IVec2::new(0, 0));
+        assert_eq!(loaded_chunk_coords(&app), vec![IVec2::new(0, 0), IVec2::new(1, 0)]);
+        let first = app.world().resource::<LoadedChunks>().0[&IVec2::new(0, 0)];
+
+        move_camera_to_chunk(&mut app, IVec2::new(3, 0));
+        assert_eq!(loaded_chunk_coords(&app), vec![IVec2::new(2, 0), IVec2::new(3, 0), IVec2::new(4, 0)]);
+        assert!(app.world().get_entity(first).is_err());
+        let kept = app.world().resource::<LoadedChunks>().0[&IVec2::new(4, 0)];
+
+        // Chunks that stay in range keep their entity, and nothing is spawned past the map edge.
+        move_camera_to_chunk(&mut app, IVec2::new(5, 0));
+        assert_eq!(loaded_chunk_coords(&app), vec![IVec2::new(4, 0), IVec2::new(5, 0)]);
+        assert_eq!(app.world().resource::<LoadedChunks>().0[&IVec2::new(4, 0)], kept);
+    }
+}
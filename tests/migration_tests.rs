@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use rpggame::binary_map::VERSION;
+    use rpggame::map::{Map, MapError};
+    use rpggame::migrations::{detect_version, CURRENT_VERSION};
+
+    const HISTORICAL_FIXTURES: [&str; 3] = [
+        "tests/fixtures/schema/v0.map.json",
+        "tests/fixtures/schema/v1.map.json",
+        "tests/fixtures/schema/v2.map.json",
+    ];
+    // Written by the first binary layout, which did not store the schema version.
+    const BINARY_V1_FIXTURE: &str = "tests/fixtures/schema/v2.map.bin";
+
+    #[test]
+    fn test_fixtures_cover_every_version() {
+        for (version, path) in HISTORICAL_FIXTURES.iter().enumerate() {
+            let value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+            assert_eq!(detect_version(&value), version as u64);
+        }
+        assert_eq!(HISTORICAL_FIXTURES.len() as u64, CURRENT_VERSION + 1);
+    }
+
+    #[test]
+    fn test_every_historical_format_migrates_to_the_same_map() {
+        let current = Map::load_from_json(HISTORICAL_FIXTURES[CURRENT_VERSION as usize]).unwrap();
+
+        for path in HISTORICAL_FIXTURES {
+            assert_eq!(Map::load_from_json(path).unwrap(), current, "{}", path);
+        }
+        assert_eq!(current.chunk_size, 2);
+    }
+
+    #[test]
+    fn test_saved_maps_carry_the_current_version() {
+        let map = Map::load_from_json(HISTORICAL_FIXTURES[0]).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&map.to_json_string().unwrap()).unwrap();
+
+        assert_eq!(value["version"], CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let json = r#"{ "version": 99, "chunk_size": 0, "chunks": [] }"#;
+
+        let result = Map::from_json_str(json);
+
+        assert!(matches!(result, Err(MapError::UnsupportedVersion { found: 99, .. })));
+    }
+
+    #[test]
+    fn test_old_binary_maps_migrate_to_the_same_map() {
+        let current = Map::load_from_json(HISTORICAL_FIXTURES[CURRENT_VERSION as usize]).unwrap();
+
+        assert_eq!(std::fs::read(BINARY_V1_FIXTURE).unwrap()[4], 1);
+        assert_eq!(Map::load_from_file(BINARY_V1_FIXTURE).unwrap(), current);
+
+        // The schema version follows the flags byte; every older palette decodes to the same blocks.
+        let mut bytes = current.to_binary(false).unwrap();
+        assert_eq!((bytes[4], bytes[6] as u64), (VERSION, CURRENT_VERSION));
+        for version in 0..=CURRENT_VERSION {
+            bytes[6] = version as u8;
+            assert_eq!(Map::from_binary(&bytes).unwrap(), current, "schema version {}", version);
+        }
+    }
+
+    #[test]
+    fn test_newer_binary_version_is_rejected() {
+        let mut bytes = b"RPGM".to_vec();
+        bytes.extend_from_slice(&[VERSION, 0, 99, 0, 0, 0]);
+
+        let result = Map::from_binary(&bytes);
+
+        assert!(matches!(result, Err(MapError::UnsupportedVersion { found: 99, .. })));
+    }
+}
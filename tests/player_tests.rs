@@ -1,18 +1,72 @@
 #[cfg(test)]
 mod tests {
-    use crate::player::should_player_move;
+    use std::collections::{BTreeMap, HashMap};
+    use bevy::prelude::*;
+    use rpggame::map::{Block, Chunk, Collision, Map};
+    use rpggame::player::{collision_corners, move_player, movement_direction, should_player_move, MOVEMENT_DELAY};
+    use rpggame::resolution::Resolution;
+    use rpggame::tileset::{TileOverrides, TileProperties, TilesetDefinition, TilesetManifest};
 
-    const MOVEMENT_DELAY: f32 = 0.5;
+    fn test_resolution() -> Resolution {
+        Resolution {
+            screen_dimensions: Vec2::new(1920.0, 1080.0),
+            pixel_ratio: 4,
+            frame_size: UVec2::new(16, 16),
+            map_translation: Vec2::new(64.0, -64.0),
+        }
+    }
+
+    fn test_tilesets() -> TilesetManifest {
+        let mut tiles = HashMap::new();
+        tiles.insert(1, TileProperties { movement_cost: 2.0, ..default() });
+
+        TilesetManifest {
+            tilesets: vec![TilesetDefinition {
+                name: String::from("grass"),
+                image: String::from("nature/ground/nature_ground.png"),
+                tile_size: [16, 16],
+                columns: 11,
+                rows: 7,
+                spacing: [0, 0],
+                offset: [0, 0],
+                tiles,
+                autotiles: HashMap::new(),
+            }],
+        }
+    }
+
+    fn test_map(collisions: [[Collision; 3]; 3]) -> Map {
+        let blocks = collisions
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|collision| Block {
+                        tile: 57,
+                        texture: String::from("grass"),
+                        overrides: TileOverrides { collision: Some(collision), ..default() },
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Map { chunk_size: 3, chunks: vec![vec![Chunk { blocks, layers: BTreeMap::new() }]] }
+    }
+
+    fn pressed(key: KeyCode) -> ButtonInput<KeyCode> {
+        let mut input = ButtonInput::default();
+        input.press(key);
+        input
+    }
 
     #[test]
     fn test_should_player_move_not_moving() {
-        let current_time = 1.0;
         let last_movement = 0.6;
+        let current_time = last_movement + MOVEMENT_DELAY / 2.0;
         let is_moving = false;
 
         let result = should_player_move(current_time, last_movement, is_moving);
 
-        assert_eq!(result, false);
+        assert!(!result);
     }
 
     #[test]
@@ -22,7 +76,7 @@ mod tests {
         let is_moving = false;
 
         let result = should_player_move(current_time, last_movement, is_moving);
-        assert_eq!(result, true);
+        assert!(result);
     }
 
     #[test]
@@ -32,6 +86,120 @@ mod tests {
         let is_moving = true;
 
         let result = should_player_move(current_time, last_movement, is_moving);
-        assert_eq!(result, true);
+        assert!(result);
+    }
+
+    #[test]
+    fn test_move_player_passable() {
+        use Collision::*;
+        let map = test_map([[Passable, Passable, Passable], [Passable, Passable, Passable], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(64.0, -64.0, 100.0);
+
+        let moved = move_player(&pressed(KeyCode::KeyD), &mut transform, 0.1, &map, &test_tilesets(), &test_resolution());
+
+        assert!(moved);
+        assert_eq!(transform.translation, Vec3::new(94.0, -64.0, 100.0));
+    }
+
+    #[test]
+    fn test_move_player_blocked_by_full() {
+        use Collision::*;
+        let map = test_map([[Passable, Passable, Passable], [Passable, Passable, Full], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(64.0, -64.0, 100.0);
+
+        let moved = move_player(&pressed(KeyCode::KeyD), &mut transform, 0.15, &map, &test_tilesets(), &test_resolution());
+
+        assert!(!moved);
+        assert_eq!(transform.translation, Vec3::new(64.0, -64.0, 100.0));
+    }
+
+    #[test]
+    fn test_move_player_blocked_by_middle() {
+        use Collision::*;
+        let map = test_map([[Passable, Middle, Passable], [Passable, Passable, Passable], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(64.0, -64.0, 100.0);
+
+        let moved = move_player(&pressed(KeyCode::KeyW), &mut transform, 0.15, &map, &test_tilesets(), &test_resolution());
+
+        assert!(!moved);
+        assert_eq!(transform.translation, Vec3::new(64.0, -64.0, 100.0));
+        assert!(!Middle.blocks_projectiles());
+    }
+
+    #[test]
+    fn test_move_player_blocked_by_its_edge() {
+        use Collision::*;
+        let map = test_map([[Passable, Passable, Passable], [Passable, Passable, Full], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(64.0, -64.0, 100.0);
+
+        // The centre would stay in the middle block, but the right edge of the player reaches the wall.
+        let moved = move_player(&pressed(KeyCode::KeyD), &mut transform, 0.05, &map, &test_tilesets(), &test_resolution());
+
+        assert!(!moved);
+        assert_eq!(transform.translation, Vec3::new(64.0, -64.0, 100.0));
+    }
+
+    #[test]
+    fn test_move_player_cannot_clip_corners() {
+        use Collision::*;
+        let map = test_map([[Passable, Passable, Full], [Passable, Passable, Passable], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(80.0, -64.0, 100.0);
+
+        let moved = move_player(&pressed(KeyCode::KeyW), &mut transform, 0.1, &map, &test_tilesets(), &test_resolution());
+
+        assert!(!moved);
+        assert_eq!(collision_corners(Vec2::new(80.0, -34.0), &test_resolution())[3], Vec2::new(104.0, -10.0));
+    }
+
+    #[test]
+    fn test_move_player_slowed() {
+        use Collision::*;
+        let map = test_map([[Passable, Passable, Passable], [Passable, Slow, Passable], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(64.0, -64.0, 100.0);
+
+        let moved = move_player(&pressed(KeyCode::KeyS), &mut transform, 0.1, &map, &test_tilesets(), &test_resolution());
+
+        assert!(moved);
+        assert_eq!(transform.translation, Vec3::new(64.0, -79.0, 100.0));
+    }
+
+    #[test]
+    fn test_move_player_uses_tileset_movement_cost() {
+        use Collision::*;
+        let mut map = test_map([[Passable, Passable, Passable], [Passable, Passable, Passable], [Passable, Passable, Passable]]);
+        map.chunks[0][0].blocks[1][1] = Block { tile: 1, texture: String::from("grass"), overrides: TileOverrides::default() };
+        let mut transform = Transform::from_xyz(64.0, -64.0, 100.0);
+
+        let moved = move_player(&pressed(KeyCode::KeyD), &mut transform, 0.1, &map, &test_tilesets(), &test_resolution());
+
+        assert!(moved);
+        assert_eq!(transform.translation, Vec3::new(79.0, -64.0, 100.0));
+    }
+
+    #[test]
+    fn test_move_player_outside_map() {
+        use Collision::*;
+        let map = test_map([[Passable, Passable, Passable], [Passable, Passable, Passable], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(0.0, 0.0, 100.0);
+
+        let moved = move_player(&pressed(KeyCode::KeyA), &mut transform, 0.15, &map, &test_tilesets(), &test_resolution());
+
+        assert!(!moved);
+        assert_eq!(transform.translation, Vec3::new(0.0, 0.0, 100.0));
+    }
+
+    #[test]
+    fn test_ctrl_shortcuts_do_not_move_the_player() {
+        use Collision::*;
+        let map = test_map([[Passable, Passable, Passable], [Passable, Passable, Passable], [Passable, Passable, Passable]]);
+        let mut transform = Transform::from_xyz(64.0, -64.0, 100.0);
+        let mut input = pressed(KeyCode::ControlLeft);
+        input.press(KeyCode::KeyS);
+
+        let moved = move_player(&input, &mut transform, 0.1, &map, &test_tilesets(), &test_resolution());
+
+        assert!(!moved);
+        assert_eq!(transform.translation, Vec3::new(64.0, -64.0, 100.0));
+        assert_eq!(movement_direction(&pressed(KeyCode::KeyS)), Some(Vec2::NEG_Y));
     }
 }
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use rpggame::generator::{generate, Biome, GeneratorParams};
+    use rpggame::map::{Collision, Map, MapLayer};
+    use rpggame::map_render::{layer_image_path, load_tileset_images, render_map, render_map_layers, RenderOptions};
+    use rpggame::tileset::TilesetManifest;
+
+    fn render(path: &str, options: &RenderOptions) -> image::RgbaImage {
+        let map = Map::load_from_file(path).unwrap();
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let images = load_tileset_images(&tilesets, "assets").unwrap();
+        render_map(&map, &tilesets, &images, options).unwrap()
+    }
+
+    #[test]
+    fn test_generated_map_matches_golden_image() {
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let params = GeneratorParams {
+            seed: 7,
+            size_in_chunks: [2, 2],
+            chunk_size: 8,
+            biomes: vec![Biome { texture: String::from("grass"), terrain: String::from("grass"), weight: 1.0 }],
+            noise_scale: 4.0,
+            decoration_chance: 0.0,
+        };
+        let map = generate(&params, &tilesets).unwrap();
+        let images = load_tileset_images(&tilesets, "assets").unwrap();
+
+        let rendered = render_map(&map, &tilesets, &images, &RenderOptions { collision_overlay: true, ..Default::default() }).unwrap();
+
+        let golden = image::open("tests/fixtures/generated_seed_7.png").unwrap().to_rgba8();
+        assert_eq!(rendered.dimensions(), golden.dimensions());
+        assert!(rendered.pixels().eq(golden.pixels()));
+    }
+
+    #[test]
+    fn test_render_size_follows_map() {
+        let rendered = render("assets/maps/main.map.json", &RenderOptions { cell_size: 8, ..Default::default() });
+
+        assert_eq!(rendered.dimensions(), (16 * 8, 16 * 8));
+    }
+
+    #[test]
+    fn test_render_single_layer() {
+        let options = RenderOptions { layers: vec![MapLayer::Objects], ..Default::default() };
+
+        let rendered = render("assets/maps/main.map.json", &options);
+
+        assert!(rendered.pixels().all(|pixel| pixel.0[3] == 0));
+    }
+
+    #[test]
+    fn test_render_collision_overlay_tints_blocked_cells() {
+        let mut map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        map.chunks[0][0].blocks[2][3].overrides.collision = Some(Collision::Full);
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let images = load_tileset_images(&tilesets, "assets").unwrap();
+
+        let plain = render_map(&map, &tilesets, &images, &RenderOptions::default()).unwrap();
+        let overlay = render_map(&map, &tilesets, &images, &RenderOptions { collision_overlay: true, ..Default::default() }).unwrap();
+
+        assert_ne!(plain.get_pixel(3 * 16 + 8, 2 * 16 + 8), overlay.get_pixel(3 * 16 + 8, 2 * 16 + 8));
+        assert_eq!(plain.get_pixel(5 * 16 + 8, 5 * 16 + 8), overlay.get_pixel(5 * 16 + 8, 5 * 16 + 8));
+    }
+
+    #[test]
+    fn test_render_per_layer() {
+        let mut map = Map::load_from_json("assets/maps/main.map.json").unwrap();
+        let mut objects = vec![vec![None; map.chunk_size]; map.chunk_size];
+        objects[1][2] = Some(map.chunks[0][0].blocks[5][5].clone());
+        map.chunks[0][0].layers.insert(MapLayer::Objects, objects);
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let images = load_tileset_images(&tilesets, "assets").unwrap();
+        let options = RenderOptions { layers: vec![MapLayer::Objects, MapLayer::Base], ..Default::default() };
+
+        let layers = render_map_layers(&map, &tilesets, &images, &options).unwrap();
+
+        assert_eq!(layers.iter().map(|(layer, _)| *layer).collect::<Vec<_>>(), [MapLayer::Base, MapLayer::Objects]);
+        let base_only = render_map(&map, &tilesets, &images, &RenderOptions { layers: vec![MapLayer::Base], ..Default::default() }).unwrap();
+        assert_eq!(layers[0].1, base_only);
+        let objects = &layers[1].1;
+        assert_eq!(objects.dimensions(), base_only.dimensions());
+        assert_ne!(objects.get_pixel(2 * 16 + 8, 16 + 8).0[3], 0);
+        assert_eq!(objects.get_pixel(5 * 16 + 8, 5 * 16 + 8).0[3], 0);
+    }
+
+    #[test]
+    fn test_layer_image_path() {
+        assert_eq!(layer_image_path("out/preview.png", MapLayer::Objects), "out/preview.objects.png");
+        assert_eq!(layer_image_path("out.dir/preview", MapLayer::Base), "out.dir/preview.base");
+    }
+}
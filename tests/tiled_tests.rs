@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests {
+    use rpggame::map::{Block, Collision, Map, MapError, MapLayer};
+    use rpggame::tiled::{export_tiled, export_tiled_file, import_tiled, import_tiled_file, import_tiled_json, TiledImportOptions};
+    use rpggame::tileset::{TileOverrides, TilesetManifest};
+
+    const SMALL_TMJ: &str = "tests/fixtures/tiled/small.tmj";
+
+    fn options(chunk_size: usize) -> TiledImportOptions {
+        TiledImportOptions {
+            chunk_size,
+            fill: Block { tile: 57, texture: "grass".to_string(), overrides: TileOverrides::default() },
+        }
+    }
+
+    #[test]
+    fn test_import_maps_tiles_layers_and_tilesets() {
+        let import = import_tiled_file(SMALL_TMJ, &options(2)).unwrap();
+        let map = import.map;
+
+        assert_eq!(map.chunk_size, 2);
+        assert_eq!((map.chunks.len(), map.chunks[0].len()), (1, 2));
+        assert_eq!(map.block_at(0, 0).unwrap().tile, 45);
+        assert_eq!(map.block_at(2, 0).unwrap().texture, "grass");
+        assert_eq!(map.block_at(2, 0).unwrap().tile, 47);
+
+        let objects = map.chunks[0][0].layer_blocks(MapLayer::Objects);
+        assert_eq!(objects.len(), 1);
+        let (_, column, tree) = objects[0];
+        assert_eq!(column, 1);
+        assert_eq!((tree.texture.as_str(), tree.tile), ("trees", 1));
+    }
+
+    #[test]
+    fn test_import_maps_custom_properties_onto_collision() {
+        let map = import_tiled_file(SMALL_TMJ, &options(2)).unwrap().map;
+
+        assert_eq!(map.block_at(0, 1).unwrap().overrides.collision, Some(Collision::Slow));
+        assert_eq!(map.block_at(0, 0).unwrap().overrides.collision, None);
+        let (_, _, tree) = map.chunks[0][0].layer_blocks(MapLayer::Objects)[0];
+        assert_eq!(tree.overrides.collision, Some(Collision::Full));
+    }
+
+    #[test]
+    fn test_import_reports_unsupported_features() {
+        let import = import_tiled_file(SMALL_TMJ, &options(2)).unwrap();
+        let warnings = import.warnings.join("\n");
+
+        assert!(warnings.contains("objectgroup \"spawns\""), "{}", warnings);
+        assert!(warnings.contains("\"Shadows\" does not match"), "{}", warnings);
+        assert!(warnings.contains("1 flipped"), "{}", warnings);
+        assert!(warnings.contains("3 empty Base cells"), "{}", warnings);
+        assert!(warnings.contains("external tileset \"../tilesets/trees.tsj\""), "{}", warnings);
+        assert_eq!(import.map.block_at(3, 1).unwrap().tile, 57);
+    }
+
+    #[test]
+    fn test_import_rejects_maps_it_cannot_represent() {
+        let infinite = r#"{"width": 1, "height": 1, "tilewidth": 16, "tileheight": 16, "infinite": true, "layers": [], "tilesets": []}"#;
+        assert!(matches!(import_tiled_json(infinite, &options(2)), Err(MapError::Import(_))));
+
+        let base64 = r#"{"width": 1, "height": 1, "tilewidth": 16, "tileheight": 16, "tilesets": [],
+            "layers": [{"type": "tilelayer", "name": "Base", "width": 1, "height": 1, "encoding": "base64", "data": "AQAAAA=="}]}"#;
+        assert!(matches!(import_tiled_json(base64, &options(2)), Err(MapError::Import(_))));
+
+        assert!(matches!(import_tiled_file("maps/example.tmx", &options(2)), Err(MapError::Import(_))));
+    }
+
+    #[test]
+    fn test_import_reports_properties_it_cannot_use() {
+        let json = r#"{"width": 1, "height": 1, "tilewidth": 16, "tileheight": 16,
+            "tilesets": [
+                {"firstgid": 1, "name": "grass", "tiles": [
+                    {"id": 0, "properties": [{"name": "collision", "type": "string", "value": "Slow"}, {"name": "sound", "type": "string", "value": "step"}]},
+                    {"id": 1, "properties": [{"name": "collision", "type": "string", "value": "bouncy"}]}
+                ]},
+                {"firstgid": 100, "source": "../tilesets/trees.tsx"}
+            ],
+            "layers": [{"type": "tilelayer", "name": "Base", "width": 1, "height": 1, "data": [1],
+                "properties": [{"name": "map_layer", "type": "string", "value": "Base"}, {"name": "light", "type": "float", "value": 0.5}]}]}"#;
+
+        let import = import_tiled_json(json, &options(1)).unwrap();
+        let warnings = import.warnings.join("\n");
+
+        assert!(warnings.contains("external tileset \"../tilesets/trees.tsx\""), "{}", warnings);
+        assert!(warnings.contains("tile properties [\"sound\"] of tileset \"grass\""), "{}", warnings);
+        assert!(warnings.contains("1 tiles of tileset \"grass\" have an unknown collision"), "{}", warnings);
+        assert!(warnings.contains("property \"light\" of layer \"Base\""), "{}", warnings);
+        assert!(!warnings.contains("map_layer"), "{}", warnings);
+        assert_eq!(import.map.block_at(0, 0).unwrap().overrides.collision, Some(Collision::Slow));
+    }
+
+    fn round_trip(map: &Map) -> Map {
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let exported = export_tiled(map, &tilesets).map;
+        let json = serde_json::to_string(&exported).unwrap();
+
+        let import = import_tiled_json(&json, &options(map.chunk_size)).unwrap();
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+        import.map
+    }
+
+    #[test]
+    fn test_import_export_import_round_trips() {
+        let imported = import_tiled_file(SMALL_TMJ, &options(2)).unwrap().map;
+
+        assert_eq!(round_trip(&imported), imported);
+    }
+
+    #[test]
+    fn test_generated_and_edited_maps_round_trip() {
+        let mut map = Map::load_from_json("tests/fixtures/generated_seed_7.map.json").unwrap();
+        map.block_at_mut(3, 5).unwrap().overrides.collision = Some(Collision::Middle);
+        map.chunks[1][0].layers.entry(MapLayer::Weather).or_insert_with(|| vec![vec![None; map.chunk_size]; map.chunk_size])[0][1] =
+            Some(Block { tile: 2, texture: "grass".to_string(), overrides: TileOverrides { collision: Some(Collision::Full), ..Default::default() } });
+
+        assert_eq!(round_trip(&map), map);
+    }
+
+    #[test]
+    fn test_export_writes_collision_as_custom_properties() {
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let imported = import_tiled_file(SMALL_TMJ, &options(2)).unwrap().map;
+
+        let export = export_tiled(&imported, &tilesets);
+        let names: Vec<&str> = export.map.layers.iter().map(|layer| layer.name.as_str()).collect();
+        assert_eq!(names, ["Base", "Base collision", "Objects", "Objects collision"]);
+        assert_eq!(export.map.layers[1].properties[0].name, "collision_for");
+
+        let collision = export.map.tilesets.iter().find(|tileset| tileset.name.as_deref() == Some("collision")).unwrap();
+        assert_eq!(collision.tiles[0].properties[0].value, "Full");
+        assert_eq!((export.map.width, export.map.height), (4, 2));
+        assert!(export.warnings.iter().any(|warning| warning.contains("\"trees\"")), "{:?}", export.warnings);
+        assert_eq!(import_tiled(&export.map, &options(2)).unwrap().map, imported);
+    }
+
+    #[test]
+    fn test_import_without_mapped_layers_fills_the_whole_map() {
+        let unmapped = r#"{"width": 3, "height": 2, "tilewidth": 16, "tileheight": 16, "tilesets": [],
+            "layers": [{"type": "tilelayer", "name": "Shadows", "width": 3, "height": 2, "data": [0, 0, 0, 0, 0, 0]}]}"#;
+
+        let import = import_tiled_json(unmapped, &options(2)).unwrap();
+
+        assert_eq!((import.map.chunks.len(), import.map.chunks[0].len()), (1, 2));
+        assert_eq!(import.map.block_at(2, 1), Some(&options(2).fill));
+        assert!(import.warnings.iter().any(|warning| warning.contains("8 empty Base cells")), "{:?}", import.warnings);
+    }
+
+    #[test]
+    fn test_export_file_references_images_relative_to_the_output() {
+        let tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        let map = import_tiled_file(SMALL_TMJ, &options(2)).unwrap().map;
+        let output_dir = std::env::temp_dir().join("rpgmap_export_test").join("maps");
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let output = output_dir.join("small.tmj");
+
+        export_tiled_file(&map, &tilesets, "assets", output.to_str().unwrap()).unwrap();
+
+        let exported: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        let images: Vec<&str> = exported["tilesets"].as_array().unwrap().iter().filter_map(|tileset| tileset["image"].as_str()).collect();
+        assert_eq!(images.len(), 2);
+        for image in images {
+            assert!(!image.starts_with('/'), "{}", image);
+            assert!(output_dir.join(image).is_file(), "{}", image);
+        }
+    }
+
+    #[test]
+    fn test_export_tolerates_tilesets_without_columns_or_rows() {
+        let mut tilesets = TilesetManifest::load_from_json("assets/tilesets.json").unwrap();
+        tilesets.tilesets[0].columns = 0;
+        tilesets.tilesets[0].rows = 0;
+        let map = import_tiled_file(SMALL_TMJ, &options(2)).unwrap().map;
+
+        let export = export_tiled(&map, &tilesets);
+
+        let grass = export.map.tilesets.iter().find(|tileset| tileset.name.as_deref() == Some("grass")).unwrap();
+        assert_eq!((grass.imagewidth, grass.imageheight), (Some(0), Some(0)));
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use rpggame::map::{Chunk, Map, MapLayer};
+    use rpggame::gridselector::BlockSelection;
+    use rpggame::tools::{apply_stamp, flood_fill_cells, line_cells, list_prefabs, paint_cells, rectangle_cells, save_prefab, Stamp};
+    use crate::common::{block, tilesets};
+
+    // Two 3x3 chunks side by side, with a wall of stone in column 4 except for a gap in the bottom row.
+    fn walled_map() -> Map {
+        let mut map = Map {
+            chunk_size: 3,
+            chunks: vec![vec![
+                Chunk { blocks: vec![vec![block("dirt", 0); 3]; 3], layers: Default::default() },
+                Chunk { blocks: vec![vec![block("dirt", 0); 3]; 3], layers: Default::default() },
+            ]],
+        };
+        for y in 0..2 {
+            map.set_block(4, y, MapLayer::Base, Some(block("stone", 1)));
+        }
+        map
+    }
+
+    #[test]
+    fn test_rectangle_and_line_cells() {
+        assert_eq!(rectangle_cells((1, 1), (0, 0)), [(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(line_cells((0, 0), (3, 1)), [(0, 0), (1, 0), (2, 1), (3, 1)]);
+        assert_eq!(line_cells((2, -2), (2, 0)), [(2, -2), (2, -1), (2, 0)]);
+        assert_eq!(line_cells((-1, -1), (-1, -1)), [(-1, -1)]);
+    }
+
+    #[test]
+    fn test_flood_fill_crosses_chunk_borders_and_stops_at_other_tiles() {
+        let map = walled_map();
+
+        let region = flood_fill_cells(&map, 0, 0, MapLayer::Base, 100).unwrap();
+        assert_eq!(region.len(), 16);
+        assert!(region.contains(&(5, 0)));
+        assert!(!region.contains(&(4, 0)));
+
+        assert_eq!(flood_fill_cells(&map, 4, 0, MapLayer::Base, 100).unwrap().len(), 2);
+        assert!(flood_fill_cells(&map, 0, 0, MapLayer::Base, 10).is_none());
+        assert!(flood_fill_cells(&map, -1, 0, MapLayer::Base, 100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_flood_fill_respects_the_active_layer() {
+        let mut map = walled_map();
+        let region = flood_fill_cells(&map, 0, 0, MapLayer::Objects, 100).unwrap();
+        assert_eq!(region.len(), 18);
+
+        paint_cells(&mut map, &region, MapLayer::Objects, Some(&block("flowers", 3)), &tilesets());
+        assert_eq!(map.layer_block_at(4, 1, MapLayer::Objects), Some(&block("flowers", 3)));
+        assert_eq!(map.block_at(4, 1), Some(&block("stone", 1)));
+    }
+
+    #[test]
+    fn test_stamp_copies_and_pastes_a_layer_region() {
+        let mut map = walled_map();
+        map.set_block(1, 1, MapLayer::Details, Some(block("flowers", 3)));
+
+        let stamp = Stamp::copy(&map, (1, 1), (0, 0), MapLayer::Details);
+        assert_eq!((stamp.width(), stamp.height()), (2, 2));
+
+        let changes = apply_stamp(&mut map, 4, 1, MapLayer::Details, &stamp, &tilesets());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(map.layer_block_at(5, 2, MapLayer::Details), Some(&block("flowers", 3)));
+
+        let base = Stamp::copy(&map, (3, 0), (4, 0), MapLayer::Base);
+        apply_stamp(&mut map, 0, 2, MapLayer::Base, &base, &tilesets());
+        assert_eq!(map.block_at(1, 2), Some(&block("stone", 1)));
+    }
+
+    #[test]
+    fn test_selection_bounds_are_normalized() {
+        let selection = BlockSelection { corners: Some(((4, -1), (2, 3))) };
+
+        assert_eq!(selection.bounds(), Some(((2, -1), (4, 3))));
+        assert_eq!(BlockSelection::default().bounds(), None);
+    }
+
+    #[test]
+    fn test_prefabs_are_saved_under_new_names_and_load_back() {
+        let dir = std::env::temp_dir().join(format!("rpggame_prefabs_{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let _ = std::fs::remove_dir_all(dir);
+        assert!(list_prefabs(dir).is_empty());
+
+        let mut map = walled_map();
+        map.set_block(4, 2, MapLayer::Objects, Some(block("trees", 1)));
+        let stamp = Stamp::copy(&map, (3, 1), (4, 2), MapLayer::Objects);
+
+        let first = save_prefab(&stamp, dir).unwrap();
+        let second = save_prefab(&stamp, dir).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(list_prefabs(dir), [first.clone(), second]);
+        assert_eq!(Stamp::load_from_json(&first).unwrap(), stamp);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}